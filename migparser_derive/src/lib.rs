@@ -0,0 +1,363 @@
+//! `#[derive(MigParser)]`: declares a migparser command line as a struct.
+//!
+//! Every field becomes an argument named after it (`max_jobs` -> `--max-jobs`):
+//!   - `bool` is a flag, `i32`/`u32`/`f32`/`String` are typed values,
+//!   - `Option<T>` is not necessary, `Vec<T>` is a `DataType::List` taking
+//!     any number of values,
+//!   - any other field is necessary unless it has a default (a positional
+//!     that is not necessary takes `nargs = "?"`).
+//!
+//! Fields are configured with `#[migparser(...)]`: `positional`, `long = ".."`,
+//! `short = 'c'`, `alias = ".."`, `env = ".."`, `help = ".."`, `metavar = ".."`,
+//! `nargs = N` (or `"?"`, `"*"`, `"+"`), `default = <expr>`, `store_false`,
+//! `negatable`, `count` (on a `u32`) and `append` (on a `Vec<T>`, one value
+//! per occurrence). Doc comments are used as help. On the struct, `name`,
+//! `description` (or the doc comment) and `epilog` are accepted.
+extern crate proc_macro;
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{
+    parse_macro_input, Attribute, Data, DeriveInput, Error, Expr, Fields, GenericArgument, LitChar,
+    LitInt, LitStr, PathArguments, Type,
+};
+
+#[proc_macro_derive(MigParser, attributes(migparser))]
+pub fn derive_migparser(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
+/* ++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++ */
+/* Attributes +++++++++++++++++++++++++++++++++++++++++++++++++++++++ */
+#[derive(Default)]
+struct ParserAttrs {
+    name: Option<LitStr>,
+    description: Option<String>,
+    epilog: Option<LitStr>,
+}
+
+#[derive(Default)]
+struct FieldAttrs {
+    positional: bool,
+    store_false: bool,
+    negatable: bool,
+    count: bool,
+    append: bool,
+    long: Option<String>,
+    short: Option<char>,
+    aliases: Vec<String>,
+    env: Option<LitStr>,
+    help: Option<String>,
+    metavar: Option<LitStr>,
+    nargs: Option<TokenStream2>,
+    default: Option<Expr>,
+}
+
+fn doc_comment(attrs: &[Attribute]) -> Option<String> {
+    let lines: Vec<String> = attrs
+        .iter()
+        .filter(|a| a.path().is_ident("doc"))
+        .filter_map(|a| match &a.meta {
+            syn::Meta::NameValue(nv) => match &nv.value {
+                Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(s), .. }) => {
+                    Some(s.value().trim().to_owned())
+                }
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect();
+    if lines.is_empty() {
+        None
+    } else {
+        Some(lines.join(" ").trim().to_owned())
+    }
+}
+
+fn parser_attrs(attrs: &[Attribute]) -> syn::Result<ParserAttrs> {
+    let mut ret = ParserAttrs {
+        description: doc_comment(attrs),
+        ..Default::default()
+    };
+    for attr in attrs.iter().filter(|a| a.path().is_ident("migparser")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("name") {
+                ret.name = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("description") {
+                ret.description = Some(meta.value()?.parse::<LitStr>()?.value());
+            } else if meta.path.is_ident("epilog") {
+                ret.epilog = Some(meta.value()?.parse()?);
+            } else {
+                return Err(meta.error("unknown migparser attribute"));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(ret)
+}
+
+fn field_attrs(attrs: &[Attribute]) -> syn::Result<FieldAttrs> {
+    let mut ret = FieldAttrs {
+        help: doc_comment(attrs),
+        ..Default::default()
+    };
+    for attr in attrs.iter().filter(|a| a.path().is_ident("migparser")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("positional") {
+                ret.positional = true;
+            } else if meta.path.is_ident("store_false") {
+                ret.store_false = true;
+            } else if meta.path.is_ident("negatable") {
+                ret.negatable = true;
+            } else if meta.path.is_ident("count") {
+                ret.count = true;
+            } else if meta.path.is_ident("append") {
+                ret.append = true;
+            } else if meta.path.is_ident("long") {
+                ret.long = Some(meta.value()?.parse::<LitStr>()?.value());
+            } else if meta.path.is_ident("short") {
+                ret.short = Some(meta.value()?.parse::<LitChar>()?.value());
+            } else if meta.path.is_ident("alias") {
+                ret.aliases.push(meta.value()?.parse::<LitStr>()?.value());
+            } else if meta.path.is_ident("env") {
+                ret.env = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("help") {
+                ret.help = Some(meta.value()?.parse::<LitStr>()?.value());
+            } else if meta.path.is_ident("metavar") {
+                ret.metavar = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("nargs") {
+                let value = meta.value()?;
+                ret.nargs = Some(if value.peek(LitStr) {
+                    let s: LitStr = value.parse()?;
+                    match s.value().as_str() {
+                        "?" => quote! { ::migparser::NArgs::Optional },
+                        "*" => quote! { ::migparser::NArgs::ZeroOrMore },
+                        "+" => quote! { ::migparser::NArgs::OneOrMore },
+                        _ => return Err(Error::new_spanned(s, "expected \"?\", \"*\" or \"+\"")),
+                    }
+                } else {
+                    let n: LitInt = value.parse()?;
+                    quote! { ::migparser::NArgs::Exact(#n) }
+                });
+            } else if meta.path.is_ident("default") {
+                ret.default = Some(meta.value()?.parse()?);
+            } else {
+                return Err(meta.error("unknown migparser attribute"));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(ret)
+}
+
+/* ++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++ */
+/* Types ++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++ */
+enum Kind {
+    Single,
+    Optional,
+    List,
+}
+
+/* Inner type of `Option<T>` / `Vec<T>` */
+fn wrapped_type<'a>(ty: &'a Type, wrapper: &str) -> Option<&'a Type> {
+    let Type::Path(p) = ty else { return None };
+    let segment = p.path.segments.last()?;
+    if segment.ident != wrapper {
+        return None;
+    }
+    let PathArguments::AngleBracketed(args) = &segment.arguments else { return None };
+    match args.args.first()? {
+        GenericArgument::Type(t) => Some(t),
+        _ => None,
+    }
+}
+
+fn scalar_name(ty: &Type) -> Option<String> {
+    let Type::Path(p) = ty else { return None };
+    let name = p.path.segments.last()?.ident.to_string();
+    match name.as_str() {
+        "i32" | "u32" | "f32" | "bool" | "String" => Some(name),
+        _ => None,
+    }
+}
+
+/* ++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++ */
+/* Expansion ++++++++++++++++++++++++++++++++++++++++++++++++++++++++ */
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let ident = &input.ident;
+    let fields = match &input.data {
+        Data::Struct(s) => match &s.fields {
+            Fields::Named(f) => &f.named,
+            _ => {
+                return Err(Error::new_spanned(
+                    &input.ident,
+                    "MigParser can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "MigParser can only be derived for structs",
+            ))
+        }
+    };
+
+    let p_attrs = parser_attrs(&input.attrs)?;
+    let mut setup: Vec<TokenStream2> = vec![];
+    if let Some(n) = &p_attrs.name {
+        setup.push(quote! { parser.set_program_name(#n); });
+    }
+    if let Some(d) = &p_attrs.description {
+        setup.push(quote! { parser.set_description(#d); });
+    }
+    if let Some(e) = &p_attrs.epilog {
+        setup.push(quote! { parser.set_epilog(#e); });
+    }
+
+    let mut inits: Vec<TokenStream2> = vec![];
+    for field in fields {
+        let field_ident = field.ident.as_ref().unwrap();
+        let attrs = field_attrs(&field.attrs)?;
+
+        let (kind, inner) = if let Some(t) = wrapped_type(&field.ty, "Option") {
+            (Kind::Optional, t)
+        } else if let Some(t) = wrapped_type(&field.ty, "Vec") {
+            (Kind::List, t)
+        } else {
+            (Kind::Single, &field.ty)
+        };
+        let scalar = scalar_name(inner).ok_or_else(|| {
+            Error::new_spanned(
+                &field.ty,
+                "unsupported field type: expected i32, u32, f32, bool or String, \
+                 optionally inside Option<..> or Vec<..>",
+            )
+        })?;
+
+        let long = attrs
+            .long
+            .clone()
+            .unwrap_or_else(|| field_ident.to_string().trim_start_matches("r#").replace('_', "-"));
+        let arg_name = long.trim_start_matches('-').to_owned();
+        let cl_name = if attrs.positional {
+            arg_name.clone()
+        } else {
+            format!("--{arg_name}")
+        };
+
+        if attrs.count && !(matches!(kind, Kind::Single) && scalar == "u32") {
+            return Err(Error::new_spanned(&field.ty, "`count` needs a u32 field"));
+        }
+
+        let mut arg = quote! { ::migparser::Arg::new(#cl_name) };
+        arg = match (&kind, scalar.as_str()) {
+            (Kind::List, _) => {
+                let list_type = match scalar.as_str() {
+                    "i32" => quote! { Int },
+                    "u32" => quote! { Uint },
+                    "f32" => quote! { Float },
+                    "bool" => quote! { Bool },
+                    _ => quote! { String },
+                };
+                quote! { #arg.list(::migparser::ListType::#list_type) }
+            }
+            (_, "u32") if attrs.count => quote! { #arg.count() },
+            (_, "i32") => quote! { #arg.int() },
+            (_, "u32") => quote! { #arg.uint() },
+            (_, "f32") => quote! { #arg.float() },
+            (_, "bool") if attrs.store_false => quote! { #arg.store_false() },
+            (_, "bool") => quote! { #arg.flag() },
+            _ => quote! { #arg.string() },
+        };
+        if let Some(c) = attrs.short {
+            let short = format!("-{c}");
+            arg = quote! { #arg.alias(#short) };
+        }
+        for a in &attrs.aliases {
+            arg = quote! { #arg.alias(#a) };
+        }
+        if let Some(e) = &attrs.env {
+            arg = quote! { #arg.env(#e) };
+        }
+        if let Some(h) = &attrs.help {
+            arg = quote! { #arg.help(#h) };
+        }
+        if let Some(m) = &attrs.metavar {
+            arg = quote! { #arg.metavar(#m) };
+        }
+        if attrs.negatable {
+            arg = quote! { #arg.negatable() };
+        }
+        if attrs.append {
+            arg = quote! { #arg.append() };
+        }
+        /* Lists take any number of values unless told otherwise, and a
+           positional that can be left out takes zero or one */
+        match &attrs.nargs {
+            Some(n) => arg = quote! { #arg.num_args(#n) },
+            None if matches!(kind, Kind::List) && !attrs.append => {
+                arg = quote! { #arg.num_args(::migparser::NArgs::ZeroOrMore) }
+            }
+            None if attrs.positional && (matches!(kind, Kind::Optional) || attrs.default.is_some()) => {
+                arg = quote! { #arg.num_args(::migparser::NArgs::Optional) }
+            }
+            None => {}
+        }
+        if let Some(d) = &attrs.default {
+            /* Typed after the field so that `default = 2` fits a u32, but a
+               string literal is a &str whatever the field */
+            let string_literal = matches!(d, Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(_), .. }));
+            arg = match kind {
+                _ if string_literal => quote! { #arg.default(#d) },
+                Kind::List => quote! { #arg.default::<::std::vec::Vec<#inner>>(#d) },
+                _ => quote! { #arg.default::<#inner>(#d) },
+            };
+        }
+        let necessary = matches!(kind, Kind::Single)
+            && scalar != "bool"
+            && !attrs.count
+            && attrs.default.is_none();
+        if necessary && !attrs.positional {
+            arg = quote! { #arg.required() };
+        }
+        setup.push(quote! {
+            parser.arg(#arg).expect("invalid MigParser argument");
+        });
+
+        let init = match kind {
+            Kind::Optional => quote! { args.get_value::<#inner>(#arg_name) },
+            Kind::List => {
+                quote! { args.get_value::<::std::vec::Vec<#inner>>(#arg_name).unwrap_or_default() }
+            }
+            Kind::Single => quote! {
+                args.get_value::<#inner>(#arg_name).ok_or_else(|| {
+                    ::migparser::ParseError::MissingRequired { arg: #arg_name.to_owned() }
+                })?
+            },
+        };
+        inits.push(quote! { #field_ident: #init });
+    }
+
+    Ok(quote! {
+        impl ::migparser::MigParser for #ident {
+            fn parser() -> ::migparser::ArgumentParser {
+                let mut parser = ::migparser::ArgumentParser::new();
+                #(#setup)*
+                parser
+            }
+
+            fn from_parsed(
+                args: &::migparser::ParsedArgs,
+            ) -> ::std::result::Result<Self, ::migparser::ParseError> {
+                ::std::result::Result::Ok(#ident {
+                    #(#inits),*
+                })
+            }
+        }
+    })
+}
@@ -0,0 +1,123 @@
+use std::mem;
+
+use super::{ArgumentOption, Content, DataType, DuplicatePolicy, ListType, NArgs};
+
+/// Fluent description of an argument, added to a parser with `ArgumentParser::arg`.
+///
+/// The argument type follows the same rules as `add_argument`: names starting
+/// with `-` are optionals (or flags when boolean), anything else is positional.
+/// e.g. `Arg::new("--necessaryarg").alias("-na").int().required().help("...")`
+#[derive(Debug, Clone)]
+pub struct Arg {
+    pub(crate) name: String,
+    pub(crate) aliases: Vec<String>,
+    pub(crate) data_type: DataType,
+    pub(crate) options: Vec<ArgumentOption>,
+    pub(crate) default_value: Option<Content>,
+}
+
+impl Arg {
+    pub fn new(name: &str) -> Self {
+        Arg {
+            name: name.to_owned(),
+            aliases: vec![],
+            data_type: DataType::String,
+            options: vec![],
+            default_value: None,
+        }
+    }
+
+    /* Identifiers */
+    pub fn alias(mut self, alias: &str) -> Self {
+        self.aliases.push(alias.to_owned());
+        self
+    }
+
+    /* Data type */
+    pub fn data_type(mut self, data_type: DataType) -> Self {
+        self.data_type = data_type;
+        self
+    }
+    pub fn int(self) -> Self {
+        self.data_type(DataType::Int)
+    }
+    pub fn uint(self) -> Self {
+        self.data_type(DataType::Uint)
+    }
+    pub fn float(self) -> Self {
+        self.data_type(DataType::Float)
+    }
+    pub fn string(self) -> Self {
+        self.data_type(DataType::String)
+    }
+    pub fn list(self, list_type: ListType) -> Self {
+        self.data_type(DataType::List(list_type))
+    }
+    /* Boolean flag, set to true when present */
+    pub fn flag(self) -> Self {
+        self.data_type(DataType::Bool).option(ArgumentOption::StoreTrue)
+    }
+    /* Boolean flag, set to false when present */
+    pub fn store_false(self) -> Self {
+        self.data_type(DataType::Bool).option(ArgumentOption::StoreFalse)
+    }
+    /* Flag also accepting --no-<name> to get the opposite value */
+    pub fn negatable(self) -> Self {
+        self.option(ArgumentOption::Negatable)
+    }
+    /* Flag counting how many times it is given */
+    pub fn count(self) -> Self {
+        self.data_type(DataType::Uint).option(ArgumentOption::Count)
+    }
+
+    /* Options. One of each kind: the last one given replaces the others,
+       e.g. .help("a").help("b") keeps "b". */
+    pub fn option(mut self, option: ArgumentOption) -> Self {
+        self.options
+            .retain(|o| mem::discriminant(o) != mem::discriminant(&option));
+        self.options.push(option);
+        self
+    }
+    pub fn required(self) -> Self {
+        self.option(ArgumentOption::Necessary)
+    }
+    /* Exact number of values */
+    pub fn nargs(self, n: usize) -> Self {
+        self.num_args(NArgs::Exact(n))
+    }
+    /* Any number of values, e.g. NArgs::OneOrMore */
+    pub fn num_args(self, n_args: NArgs) -> Self {
+        self.option(ArgumentOption::NArgs(n_args))
+    }
+    /* Positional list taking everything after `--` */
+    pub fn trailing(self) -> Self {
+        self.option(ArgumentOption::Trailing).num_args(NArgs::ZeroOrMore)
+    }
+    /* List optional given once per value: -I a -I b */
+    pub fn append(self) -> Self {
+        self.option(ArgumentOption::Append)
+    }
+    /* List optional collecting the values of every occurrence */
+    pub fn extend(self) -> Self {
+        self.option(ArgumentOption::Extend)
+    }
+    /* When given several times, instead of the parser policy */
+    pub fn duplicates(self, policy: DuplicatePolicy) -> Self {
+        self.option(ArgumentOption::Duplicates(policy))
+    }
+    /* Environment variable used when absent from the command line */
+    pub fn env(self, var: &str) -> Self {
+        self.option(ArgumentOption::Env(var.to_owned()))
+    }
+    pub fn help(self, help: &str) -> Self {
+        self.option(ArgumentOption::Help(help.to_owned()))
+    }
+    pub fn metavar(self, metavar: &str) -> Self {
+        self.option(ArgumentOption::Metavar(metavar.to_owned()))
+    }
+
+    pub fn default<T: Into<Content>>(mut self, value: T) -> Self {
+        self.default_value = Some(value.into());
+        self
+    }
+}
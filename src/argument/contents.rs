@@ -13,6 +13,17 @@ pub enum ListType {
     Bool,
     Float,
 }
+impl ListType {
+    pub fn data_type(&self) -> DataType {
+        match self {
+            ListType::Bool => DataType::Bool,
+            ListType::Int => DataType::Int,
+            ListType::Uint => DataType::Uint,
+            ListType::String => DataType::String,
+            ListType::Float => DataType::Float,
+        }
+    }
+}
 // Supported types
 #[derive(Debug)]
 #[derive(Clone)]
@@ -83,19 +94,85 @@ impl Content {
             Content::Uint(_) => DataType::Uint,
             Content::String(_) => DataType::String,
             Content::Float(_) => DataType::Float,
-            Content::List(t) => {
-                match &t.data_type {
-                    ListType::Bool => {DataType::Bool},
-                    ListType::Int => {DataType::Int},
-                    ListType::Uint => {DataType::Uint},
-                    ListType::String => {DataType::String},
-                    ListType::Float => {DataType::Float},
-                }
-            },
+            Content::List(t) => t.data_type.data_type(),
         }
     }
 }
 
+/* Conversions used to give default values, e.g. Arg::default(3) */
+impl From<i32> for Content {
+    fn from(value: i32) -> Self {
+        Content::Int(value)
+    }
+}
+
+impl From<u32> for Content {
+    fn from(value: u32) -> Self {
+        Content::Uint(value)
+    }
+}
+
+impl From<f32> for Content {
+    fn from(value: f32) -> Self {
+        Content::Float(value)
+    }
+}
+
+impl From<bool> for Content {
+    fn from(value: bool) -> Self {
+        Content::Bool(value)
+    }
+}
+
+impl From<String> for Content {
+    fn from(value: String) -> Self {
+        Content::String(value)
+    }
+}
+
+impl From<&str> for Content {
+    fn from(value: &str) -> Self {
+        Content::String(value.to_owned())
+    }
+}
+
+fn list_from<T: Into<Content>>(data_type: ListType, values: Vec<T>) -> Content {
+    Content::List(ContentList {
+        data_type,
+        data: values.into_iter().map(|v| v.into()).collect(),
+    })
+}
+
+impl From<Vec<i32>> for Content {
+    fn from(value: Vec<i32>) -> Self {
+        list_from(ListType::Int, value)
+    }
+}
+
+impl From<Vec<u32>> for Content {
+    fn from(value: Vec<u32>) -> Self {
+        list_from(ListType::Uint, value)
+    }
+}
+
+impl From<Vec<f32>> for Content {
+    fn from(value: Vec<f32>) -> Self {
+        list_from(ListType::Float, value)
+    }
+}
+
+impl From<Vec<bool>> for Content {
+    fn from(value: Vec<bool>) -> Self {
+        list_from(ListType::Bool, value)
+    }
+}
+
+impl From<Vec<String>> for Content {
+    fn from(value: Vec<String>) -> Self {
+        list_from(ListType::String, value)
+    }
+}
+
 pub trait ExtractFromContents {
     fn extract(object: &Content) -> Option<Self> where Self: Sized;
 }
@@ -1,4 +1,6 @@
+mod arg;
 mod contents;
+pub use arg::Arg;
 pub use contents::Content;
 pub use contents::{DataType, ExtractFromContents, ListType, ContentList};
 /*
@@ -6,7 +8,7 @@ TODO:
    - ArgumentOptions -> to enum
    - cl_name -> to cl_indetifiers ?
    - new() : and different creators for different argument types
-   - encapsulation of parsed, index, data (protected components)
+   - encapsulation of parsed, data (protected components)
 
 */
 #[derive(PartialEq)]
@@ -18,6 +20,53 @@ pub enum ArgumentType {
     Flag,
 }
 
+/// Number of values taken by an argument, like Python's `nargs`.
+#[derive(PartialEq)]
+#[derive(Debug)]
+#[derive(Clone, Copy)]
+pub enum NArgs {
+    Exact(usize),
+    /// `?`: zero or one value.
+    Optional,
+    /// `*`
+    ZeroOrMore,
+    /// `+`
+    OneOrMore,
+    /// Between min and max values, both included.
+    Range(usize, usize),
+}
+impl NArgs {
+    pub fn min(&self) -> usize {
+        match self {
+            NArgs::Exact(n) => *n,
+            NArgs::Optional | NArgs::ZeroOrMore => 0,
+            NArgs::OneOrMore => 1,
+            NArgs::Range(min, _) => *min,
+        }
+    }
+    /* None when unbounded */
+    pub fn max(&self) -> Option<usize> {
+        match self {
+            NArgs::Exact(n) => Some(*n),
+            NArgs::Optional => Some(1),
+            NArgs::ZeroOrMore | NArgs::OneOrMore => None,
+            NArgs::Range(_, max) => Some(*max),
+        }
+    }
+}
+
+/// What to do when a flag or single-valued optional is given several times.
+#[derive(PartialEq)]
+#[derive(Debug)]
+#[derive(Clone, Copy, Default)]
+pub enum DuplicatePolicy {
+    #[default]
+    FirstWins,
+    LastWins,
+    /// `ParseError::DuplicateArgument`
+    Error,
+}
+
 #[derive(PartialEq)]
 #[derive(Debug)]
 #[derive(Clone)]
@@ -25,7 +74,23 @@ pub enum ArgumentOption {
     StoreTrue,
     StoreFalse,
     Necessary,
-    NArgs(usize),
+    NArgs(NArgs),
+    Help(String),
+    Metavar(String),
+    /// Positional list receiving the arguments given after `--`.
+    Trailing,
+    /// Flag counting its occurrences (`-vvv` is 3), as a `Content::Uint`.
+    Count,
+    /// List optional taking one value per occurrence (`-I a -I b`).
+    Append,
+    /// List optional adding all the values of every occurrence.
+    Extend,
+    /// Overrides the parser duplicate policy for this argument.
+    Duplicates(DuplicatePolicy),
+    /// Flag also accepting `--no-<name>`, the last occurrence winning.
+    Negatable,
+    /// Environment variable read when the argument is not on the command line.
+    Env(String),
 }
 
 #[derive(Debug, Clone)]
@@ -33,12 +98,10 @@ pub struct Argument {
     pub name: String,
     pub cl_identifiers: Vec<String>,
     pub data_type: DataType,
-    data: Option<Content>,
+    default_value: Option<Content>,
     pub options: Vec<ArgumentOption>,
-    parsed: bool,
-    index: i32,
     arg_type: ArgumentType,
-    pub n_args: usize
+    pub n_args: NArgs
 }
 impl Argument {
     /* Creators
@@ -53,19 +116,16 @@ impl Argument {
         cl_identifiers_: Vec<String>,
         data_type_: DataType,
         options_: Option<Vec<ArgumentOption>>,
-        index_: i32,
         default_val: Option<Content>,
         arg_type_: ArgumentType,
-        n_args_ : usize
+        n_args_ : NArgs
     ) -> Self {
         Argument {
             name: name_.to_owned(),
             cl_identifiers: cl_identifiers_,
             data_type: data_type_,
-            data: default_val,
+            default_value: default_val,
             options: options_.unwrap_or_default(),
-            parsed: false,
-            index: index_, /* Only settable at instantiation new_positional */
             arg_type: arg_type_,
             n_args: n_args_
         }
@@ -76,9 +136,9 @@ impl Argument {
         data_type_: DataType,
         options_: Option<Vec<ArgumentOption>>,
         default_val: Option<Content>,
-        n_args_ : usize
+        n_args_ : NArgs
     ) -> Self {
-        Argument::new(name_, cl_identifiers_, data_type_, options_, -1, default_val, ArgumentType::Optional, n_args_)
+        Argument::new(name_, cl_identifiers_, data_type_, options_, default_val, ArgumentType::Optional, n_args_)
     }
 
     pub fn new_positional(
@@ -86,9 +146,10 @@ impl Argument {
         cl_identifiers_: Vec<String>,
         data_type_: DataType,
         options_: Option<Vec<ArgumentOption>>,
-        index_: i32,
+        default_val: Option<Content>,
     ) -> Self {
-        Argument::new(name_, cl_identifiers_, data_type_, options_, index_, None, ArgumentType::Positional, 1)
+        let n_args = Argument::get_n_args(options_.as_deref().unwrap_or_default());
+        Argument::new(name_, cl_identifiers_, data_type_, options_, default_val, ArgumentType::Positional, n_args)
     }
 
     pub fn new_flag(
@@ -97,65 +158,80 @@ impl Argument {
         options_: Option<Vec<ArgumentOption>>,
         default_val: Option<Content>
     ) -> Self {
-        Argument::new(name_, cl_identifiers_, DataType::Bool, options_, -1, default_val, ArgumentType::Flag, 1)
+        let data_type = match &options_ {
+            Some(o) if o.contains(&ArgumentOption::Count) => DataType::Uint,
+            _ => DataType::Bool,
+        };
+        Argument::new(name_, cl_identifiers_, data_type, options_, default_val, ArgumentType::Flag, NArgs::Exact(0))
     }
 
     /* AUX */
-    pub fn get_data(&self) -> Option<Content> {
-        self.data.clone()
+    pub fn get_default(&self) -> Option<Content> {
+        self.default_value.clone()
     }
-    pub fn set_data(&mut self, data: Content) -> bool {
-        if self.is_parsed() {
-            return false;
+    pub fn get_help(&self) -> Option<String> {
+        self.options.iter().find_map(|o| match o {
+            ArgumentOption::Help(h) => Some(h.clone()),
+            _ => None,
+        })
+    }
+    /* Placeholder for the values in the help, NAME by default */
+    pub fn get_metavar(&self) -> String {
+        let metavar = self.options.iter().find_map(|o| match o {
+            ArgumentOption::Metavar(m) => Some(m.clone()),
+            _ => None,
+        });
+        match metavar {
+            Some(m) => m,
+            None if self.arg_type == ArgumentType::Positional => self.name.clone(),
+            None => self.name.to_uppercase(),
         }
-        // if self.data_type != data.get_type() {
-        //     return false;
-        // }
-        println!("{:?}", data);
-        self.data = Some(data);
-        return true;
     }
     pub fn has_option(&self, option: ArgumentOption) -> bool {
         self.options.contains(&option)
     }
-    pub fn is_parsed(&self) -> bool {
-        self.parsed
-    }
-
-    pub fn set_parsed(&mut self) {
-        self.parsed = true;
-    }
-
-    pub fn get_index(&self) -> i32 {
-        self.index
-    }
-    pub fn get_n_args(opts: &Vec<ArgumentOption>) -> usize{
-        let mut ret: usize = 1;
+    pub fn get_n_args(opts: &[ArgumentOption]) -> NArgs {
+        let mut ret = NArgs::Exact(1);
         for o in opts {
-            match o {
-                ArgumentOption::NArgs(i) => {if *i > 0 {ret = *i;}},
-                _ => {}
+            if let ArgumentOption::NArgs(n) = o {
+                ret = *n;
             }
         }
         ret
     }
 
-    pub fn guess_type(name: &str, options: &Vec<ArgumentOption>, data_type_: &DataType) -> Option<ArgumentType> {
+    pub fn get_env(&self) -> Option<String> {
+        self.options.iter().find_map(|o| match o {
+            ArgumentOption::Env(e) => Some(e.clone()),
+            _ => None,
+        })
+    }
+    pub fn get_duplicates(&self) -> Option<DuplicatePolicy> {
+        self.options.iter().find_map(|o| match o {
+            ArgumentOption::Duplicates(d) => Some(*d),
+            _ => None,
+        })
+    }
+
+    pub fn guess_type(name: &str, options: &[ArgumentOption], data_type_: &DataType) -> Option<ArgumentType> {
         if name.is_empty() {
             return None;
         }
         
-        if name.starts_with("-") {
+        if name.starts_with('-') {
             /* Flag or optional */
             if *data_type_ == DataType::Bool && 
                 (options.contains(&ArgumentOption::StoreFalse) || options.contains(&ArgumentOption::StoreTrue))
             {
                 return Some(ArgumentType::Flag);
             }
+            if options.contains(&ArgumentOption::Count) {
+                return Some(ArgumentType::Flag);
+            }
             return Some(ArgumentType::Optional);
         }
         
-        return Some(ArgumentType::Positional);
+        Some(ArgumentType::Positional)
     }
 
     pub fn get_type(&self) -> ArgumentType{
@@ -167,7 +243,7 @@ impl Argument {
             return None;
         }
 
-        let mut ret_name: String = name.clone().into();
+        let mut ret_name: String = name.into();
         loop {
             let minus_start = ret_name.starts_with("-");
             if minus_start {
@@ -183,4 +259,18 @@ impl Argument {
     pub fn has_identifier(&self, id: &str) -> bool {
         self.cl_identifiers.contains(&id.to_owned())
     }
+
+    /* Whether `id` is a `--no-<name>` identifier added by Negatable */
+    pub fn is_negation(&self, id: &str) -> bool {
+        self.has_option(ArgumentOption::Negatable)
+            && id
+                .strip_prefix("--no-")
+                .is_some_and(|name| self.has_identifier(&format!("--{name}")))
+    }
+
+    /* Whether a cl token is meant as an identifier (-x, --xx) rather than a
+       value. Negative numbers and a lone "-" are values. */
+    pub fn looks_like_identifier(token: &str) -> bool {
+        token.len() > 1 && token.starts_with('-') && token.parse::<f64>().is_err()
+    }
 }
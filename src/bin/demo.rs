@@ -1,50 +1,61 @@
-use migparser::{ArgumentOption, ArgumentParser, DataType, ListType};
+use migparser::{Arg, ArgumentParser, ListType, ParseError};
 
 fn main() -> Result<(), String> {
     let mut parser = ArgumentParser::new();
+    parser.set_description("Demo of the migparser argument types.");
     /* The type of argument (flag, positional, ...) is identified
        by the name of the argument and data type.
     */
-    parser.add_argument("positionalarg", None, DataType::String, None, None)?;
+    parser.arg(Arg::new("positionalarg").help("A positional string"))?;
 
-    parser.add_argument(
-        "--necessaryarg",
-        Some(vec!["-na".to_owned()]),
-        DataType::Int,
-        Some(vec![ArgumentOption::Necessary]),
-        None,
+    parser.arg(
+        Arg::new("--necessaryarg")
+            .alias("-na")
+            .int()
+            .required()
+            .help("An integer that must be given"),
     )?;
-    parser.add_argument(
-        "--optionalarg",
-        Some(vec!["-oa".to_owned()]),
-        DataType::Float,
-        None,
-        None,
+    parser.arg(
+        Arg::new("--optionalarg")
+            .alias("-oa")
+            .float()
+            .help("An optional float"),
     )?;
-    parser.add_argument(
-        "--flagarg",
-        Some(vec!["-f".to_owned()]),
-        DataType::Bool,
-        Some(vec![ArgumentOption::StoreTrue]), /* StoreTrue or StoreFalse for flag */
-        None,
+    parser.arg(
+        Arg::new("--flagarg")
+            .alias("-f")
+            .flag() /* flag() or store_false() */
+            .help("A boolean flag"),
     )?;
 
-    parser.add_argument(
-        "--listarg",
-        Some(vec!["-la".to_owned()]),
-        DataType::List(ListType::Int),
-        Some(vec![ArgumentOption::NArgs(4)]), /* StoreTrue or StoreFalse for flag */
-        None,
+    parser.arg(
+        Arg::new("--listarg")
+            .alias("-la")
+            .list(ListType::Int)
+            .nargs(4)
+            .metavar("N")
+            .help("A list of four integers"),
     )?;
 
-    let mut parser2 = parser.clone();
+    /* Report every problem of the command line at once */
+    parser.set_collect_errors(true);
 
     parser.print_data();
-    parser.parse_arguments();
+    match parser.parse_arguments() {
+        Ok(()) => {}
+        Err(ParseError::HelpRequested) => return Ok(()),
+        Err(e) => {
+            parser.print_error(&e);
+            return Err("Invalid command line".to_owned());
+        }
+    }
     parser.print_data();
 
-    parser2.parse_arguments_from_text("miguel -na 1 -f 1 -oa 2.3 -la 12 34 78 23".to_owned());
-    parser2.print_data();
+    /* The parser is not modified by parse_from_text: it can be reused */
+    let parsed = parser
+        .parse_from_text("miguel -na 1 -f -oa 2.3 -la 12 34 78 23")
+        .map_err(|e| e.to_string())?;
+    println!("{:?}", parsed.get_value::<Vec<i32>>("listarg"));
 
     Ok(())
 }
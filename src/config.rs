@@ -0,0 +1,235 @@
+//! Configuration files giving values to the arguments absent from the
+//! command line.
+//!
+//! Keys are argument names. The format follows the extension: `.toml` (with
+//! the `toml` feature), `.json` (with the `json` feature), INI otherwise.
+use std::fs;
+use std::path::Path;
+
+use crate::{Argument, ArgumentParser, Content, ContentList, DataType, ParseError};
+
+/* A value read from the file, before knowing its argument data type. INI
+   only gives single values. */
+#[cfg_attr(not(any(feature = "toml", feature = "json")), allow(dead_code))]
+enum ConfigValue {
+    Single(String),
+    List(Vec<String>),
+    /* Sections, objects, nested arrays: never an argument value */
+    Table,
+}
+
+pub(crate) struct ConfigFile {
+    path: String,
+    values: Vec<(String, ConfigValue)>,
+}
+
+impl ConfigFile {
+    pub(crate) fn load(path: &str) -> Result<ConfigFile, ParseError> {
+        let error = |message: String| ParseError::ConfigFile {
+            path: path.to_owned(),
+            message,
+        };
+        let text = fs::read_to_string(path).map_err(|e| error(e.to_string()))?;
+        let values = match Path::new(path).extension().and_then(|e| e.to_str()) {
+            Some("toml") => ConfigFile::parse_toml(&text),
+            Some("json") => ConfigFile::parse_json(&text),
+            _ => ConfigFile::parse_ini(&text),
+        }
+        .map_err(error)?;
+        Ok(ConfigFile {
+            path: path.to_owned(),
+            values,
+        })
+    }
+
+    pub(crate) fn path(&self) -> &str {
+        &self.path
+    }
+
+    /* Value of an argument, converted with its data type */
+    pub(crate) fn content(&self, argument: &Argument) -> Result<Option<Content>, ParseError> {
+        let Some((key, value)) = self.values.iter().find(|(k, _)| *k == argument.name) else {
+            return Ok(None);
+        };
+        let invalid = |raw: String| ParseError::InvalidConfigValue {
+            path: self.path.clone(),
+            key: key.clone(),
+            raw,
+            expected: argument.data_type.clone(),
+        };
+
+        match (value, &argument.data_type) {
+            (ConfigValue::Single(raw), type_) => ArgumentParser::parse_value(raw, type_)
+                .map(Some)
+                .ok_or_else(|| invalid(raw.clone())),
+            (ConfigValue::List(items), DataType::List(t)) => {
+                let mut result = ContentList::new(t.clone());
+                for item in items {
+                    let data = ArgumentParser::parse_value(item, &t.data_type())
+                        .ok_or_else(|| invalid(item.clone()))?;
+                    result.data.push(data);
+                }
+                Ok(Some(Content::List(result)))
+            }
+            (ConfigValue::List(items), _) => Err(invalid(format!("[{}]", items.join(", ")))),
+            (ConfigValue::Table, _) => Err(invalid("<table>".to_owned())),
+        }
+    }
+
+    /* key = value (or key: value) lines, # and ; comments. Sections are
+       only headers: their keys are read like the others. */
+    fn parse_ini(text: &str) -> Result<Vec<(String, ConfigValue)>, String> {
+        let mut values = vec![];
+        for (n, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+                continue;
+            }
+            if line.starts_with('[') && line.ends_with(']') {
+                continue;
+            }
+            let Some((key, value)) = line.split_once(['=', ':']) else {
+                return Err(format!("line {}: expected 'key = value'", n + 1));
+            };
+            let value = value.trim();
+            let value = value
+                .strip_prefix('"')
+                .and_then(|v| v.strip_suffix('"'))
+                .unwrap_or(value);
+            values.push((key.trim().to_owned(), ConfigValue::Single(value.to_owned())));
+        }
+        Ok(values)
+    }
+
+    #[cfg(feature = "toml")]
+    fn parse_toml(text: &str) -> Result<Vec<(String, ConfigValue)>, String> {
+        use toml::Value;
+
+        fn single(value: &Value) -> Option<String> {
+            match value {
+                Value::String(s) => Some(s.clone()),
+                Value::Integer(i) => Some(i.to_string()),
+                Value::Float(f) => Some(f.to_string()),
+                Value::Boolean(b) => Some(b.to_string()),
+                Value::Datetime(d) => Some(d.to_string()),
+                Value::Array(_) | Value::Table(_) => None,
+            }
+        }
+
+        let table: toml::Table = text.parse().map_err(|e: toml::de::Error| e.message().to_owned())?;
+        Ok(table
+            .iter()
+            .map(|(key, value)| {
+                let value = match value {
+                    Value::Array(items) => match items.iter().map(single).collect() {
+                        Some(items) => ConfigValue::List(items),
+                        None => ConfigValue::Table,
+                    },
+                    Value::Table(_) => ConfigValue::Table,
+                    v => ConfigValue::Single(single(v).unwrap()),
+                };
+                (key.clone(), value)
+            })
+            .collect())
+    }
+
+    #[cfg(not(feature = "toml"))]
+    fn parse_toml(_text: &str) -> Result<Vec<(String, ConfigValue)>, String> {
+        Err("TOML files need the `toml` feature of migparser".to_owned())
+    }
+
+    #[cfg(feature = "json")]
+    fn parse_json(text: &str) -> Result<Vec<(String, ConfigValue)>, String> {
+        use serde_json::Value;
+
+        fn single(value: &Value) -> Option<String> {
+            match value {
+                Value::String(s) => Some(s.clone()),
+                Value::Number(n) => Some(n.to_string()),
+                Value::Bool(b) => Some(b.to_string()),
+                Value::Null | Value::Array(_) | Value::Object(_) => None,
+            }
+        }
+
+        let Value::Object(object) = serde_json::from_str(text).map_err(|e| e.to_string())? else {
+            return Err("expected an object".to_owned());
+        };
+        Ok(object
+            .iter()
+            /* null is the same as absent */
+            .filter(|(_, value)| !value.is_null())
+            .map(|(key, value)| {
+                let value = match value {
+                    Value::Array(items) => match items.iter().map(single).collect() {
+                        Some(items) => ConfigValue::List(items),
+                        None => ConfigValue::Table,
+                    },
+                    Value::Object(_) => ConfigValue::Table,
+                    v => ConfigValue::Single(single(v).unwrap()),
+                };
+                (key.clone(), value)
+            })
+            .collect())
+    }
+
+    #[cfg(not(feature = "json"))]
+    fn parse_json(_text: &str) -> Result<Vec<(String, ConfigValue)>, String> {
+        Err("JSON files need the `json` feature of migparser".to_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn singles(values: Vec<(String, ConfigValue)>) -> Vec<(String, String)> {
+        values
+            .into_iter()
+            .map(|(k, v)| match v {
+                ConfigValue::Single(s) => (k, s),
+                ConfigValue::List(items) => (k, format!("[{}]", items.join(", "))),
+                ConfigValue::Table => (k, "<table>".to_owned()),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn ini() {
+        let text = "# comment\n; comment\n[section]\nlevel = 3\nname: \"a b\"\n\n";
+        let values = singles(ConfigFile::parse_ini(text).unwrap());
+        assert_eq!(values, [("level".into(), "3".into()), ("name".into(), "a b".into())]);
+        assert_eq!(ConfigFile::parse_ini("level 3").err().unwrap(), "line 1: expected 'key = value'");
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn toml() {
+        let text = "level = 3\nratios = [0.5, 1]\n[section]\nkey = 1\n";
+        let values = singles(ConfigFile::parse_toml(text).unwrap());
+        assert_eq!(
+            values,
+            [
+                ("level".into(), "3".into()),
+                ("ratios".into(), "[0.5, 1]".into()),
+                ("section".into(), "<table>".into())
+            ]
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn json() {
+        let text = r#"{"level": 3, "names": ["a", "b"], "skip": null, "nested": {}}"#;
+        let mut values = singles(ConfigFile::parse_json(text).unwrap());
+        values.sort();
+        assert_eq!(
+            values,
+            [
+                ("level".into(), "3".into()),
+                ("names".into(), "[a, b]".into()),
+                ("nested".into(), "<table>".into())
+            ]
+        );
+        assert!(ConfigFile::parse_json("[1]").is_err());
+    }
+}
@@ -0,0 +1,35 @@
+//! Struct-based command lines, see `#[derive(MigParser)]` (feature `derive`).
+use std::process;
+
+use crate::{ArgumentParser, ParseError, ParsedArgs};
+
+/// A command line declared as a struct.
+///
+/// Usually implemented with `#[derive(MigParser)]`: `parser()` registers one
+/// argument per field and `from_parsed()` fills the fields from the parsed values.
+pub trait MigParser: Sized {
+    fn parser() -> ArgumentParser;
+
+    fn from_parsed(args: &ParsedArgs) -> Result<Self, ParseError>;
+
+    fn try_from_args() -> Result<Self, ParseError> {
+        Self::from_parsed(&Self::parser().parse()?)
+    }
+
+    fn try_from_text(text: &str) -> Result<Self, ParseError> {
+        Self::from_parsed(&Self::parser().parse_from_text(text)?)
+    }
+
+    /* Exits the process after the help or on an invalid command line */
+    fn from_args() -> Self {
+        let parser = Self::parser();
+        match parser.parse().and_then(|args| Self::from_parsed(&args)) {
+            Ok(s) => s,
+            Err(ParseError::HelpRequested) => process::exit(0),
+            Err(e) => {
+                parser.print_error(&e);
+                process::exit(2);
+            }
+        }
+    }
+}
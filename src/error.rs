@@ -0,0 +1,232 @@
+use migformatting::Formatting;
+use std::fmt;
+
+use crate::argument::DataType;
+
+/// Errors produced while parsing a command line.
+///
+/// `position` is always the index of the offending token in argv
+/// (argv[0] being the program name).
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseError {
+    MissingRequired {
+        arg: String,
+    },
+    NotEnoughValues {
+        arg: String,
+        token: String,
+        position: usize,
+        expected: usize,
+        found: usize,
+    },
+    InvalidValue {
+        arg: String,
+        raw: String,
+        expected: DataType,
+        position: usize,
+    },
+    /// The environment variable of an argument holds an invalid value.
+    InvalidEnvValue {
+        arg: String,
+        var: String,
+        raw: String,
+        expected: DataType,
+    },
+    /// A configuration file cannot be read or parsed.
+    ConfigFile {
+        path: String,
+        message: String,
+    },
+    /// The value of `key` in a configuration file does not fit its argument.
+    InvalidConfigValue {
+        path: String,
+        key: String,
+        raw: String,
+        expected: DataType,
+    },
+    /// A response file (`@path`) cannot be read or split. `line` is the line
+    /// of `path` at fault, `None` for a file given in argv.
+    ResponseFile {
+        path: String,
+        line: Option<usize>,
+        message: String,
+    },
+    /// `error` is about a token read from line `line` of the response file
+    /// `path` (its position being the one in the expanded command line).
+    InResponseFile {
+        path: String,
+        line: usize,
+        error: Box<ParseError>,
+    },
+    UnknownArgument {
+        token: String,
+        position: usize,
+    },
+    /// A value was attached (`--flag=value`) to an argument taking none.
+    UnexpectedValue {
+        arg: String,
+        token: String,
+        position: usize,
+    },
+    /// An argument given again while its duplicate policy is `Error`.
+    DuplicateArgument {
+        arg: String,
+        token: String,
+        position: usize,
+    },
+    /// Two arguments of a mutually exclusive group were given, `second` at
+    /// `position`.
+    MutuallyExclusive {
+        first: String,
+        second: String,
+        position: usize,
+    },
+    /// None of the arguments of a necessary mutually exclusive group has a value.
+    MissingOneOf {
+        args: Vec<String>,
+    },
+    /// An argv token is not valid UTF-8 (`token` is shown lossily).
+    InvalidUtf8 {
+        token: String,
+        position: usize,
+    },
+    /// A quote opened at byte `offset` of a command line text is never closed.
+    UnterminatedQuote {
+        quote: char,
+        offset: usize,
+    },
+    /// `-h`/`--help` was given: the help has been printed and parsing stopped.
+    HelpRequested,
+    /// Every problem found in one pass, when the parser collects errors.
+    Multiple(Vec<ParseError>),
+}
+
+impl ParseError {
+    /* Moves the argv positions by `offset`, for errors raised by a
+       subcommand parser that only saw the tail of the command line. */
+    pub(crate) fn shifted(self, offset: usize) -> ParseError {
+        match self {
+            ParseError::NotEnoughValues { arg, token, position, expected, found } => {
+                ParseError::NotEnoughValues { arg, token, position: position + offset, expected, found }
+            }
+            ParseError::InvalidValue { arg, raw, expected, position } => {
+                ParseError::InvalidValue { arg, raw, expected, position: position + offset }
+            }
+            ParseError::UnknownArgument { token, position } => {
+                ParseError::UnknownArgument { token, position: position + offset }
+            }
+            ParseError::UnexpectedValue { arg, token, position } => {
+                ParseError::UnexpectedValue { arg, token, position: position + offset }
+            }
+            ParseError::DuplicateArgument { arg, token, position } => {
+                ParseError::DuplicateArgument { arg, token, position: position + offset }
+            }
+            ParseError::MutuallyExclusive { first, second, position } => {
+                ParseError::MutuallyExclusive { first, second, position: position + offset }
+            }
+            ParseError::InvalidUtf8 { token, position } => {
+                ParseError::InvalidUtf8 { token, position: position + offset }
+            }
+            ParseError::Multiple(errors) => {
+                ParseError::Multiple(errors.into_iter().map(|e| e.shifted(offset)).collect())
+            }
+            e => e,
+        }
+    }
+
+    /* argv index of the offending token, if the error has one */
+    pub(crate) fn position(&self) -> Option<usize> {
+        match self {
+            ParseError::NotEnoughValues { position, .. }
+            | ParseError::InvalidValue { position, .. }
+            | ParseError::UnknownArgument { position, .. }
+            | ParseError::UnexpectedValue { position, .. }
+            | ParseError::DuplicateArgument { position, .. }
+            | ParseError::MutuallyExclusive { position, .. }
+            | ParseError::InvalidUtf8 { position, .. } => Some(*position),
+            _ => None,
+        }
+    }
+
+    /// Renders the error (or all of them) for the terminal, one per line.
+    pub fn report(&self) -> String {
+        match self {
+            ParseError::Multiple(errors) => errors
+                .iter()
+                .map(|e| e.report())
+                .collect::<Vec<String>>()
+                .join("\n"),
+            _ => format!("error: {self}").error(),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::MissingRequired { arg } => {
+                write!(f, "Necessary argument '{arg}' is not present")
+            }
+            ParseError::NotEnoughValues { arg, token, position, expected, found } => write!(
+                f,
+                "Not enough values for '{arg}' ('{token}' at position {position}): expected {expected}, found {found}"
+            ),
+            ParseError::InvalidValue { arg, raw, expected, position } => write!(
+                f,
+                "Invalid value '{raw}' for '{arg}' at position {position}: expected {expected:?}"
+            ),
+            ParseError::InvalidEnvValue { arg, var, raw, expected } => write!(
+                f,
+                "Invalid value '{raw}' for '{arg}' in environment variable {var}: expected {expected:?}"
+            ),
+            ParseError::ConfigFile { path, message } => {
+                write!(f, "Invalid configuration file {path}: {message}")
+            }
+            ParseError::InvalidConfigValue { path, key, raw, expected } => write!(
+                f,
+                "Invalid value '{raw}' for '{key}' in configuration file {path}: expected {expected:?}"
+            ),
+            ParseError::ResponseFile { path, line: Some(line), message } => {
+                write!(f, "Invalid response file {path}, line {line}: {message}")
+            }
+            ParseError::ResponseFile { path, line: None, message } => {
+                write!(f, "Invalid response file {path}: {message}")
+            }
+            ParseError::InResponseFile { path, line, error } => {
+                write!(f, "{error} (response file {path}, line {line})")
+            }
+            ParseError::UnknownArgument { token, position } => {
+                write!(f, "Unknown argument '{token}' at position {position}")
+            }
+            ParseError::UnexpectedValue { arg, token, position } => {
+                write!(f, "'{arg}' takes no value ('{token}' at position {position})")
+            }
+            ParseError::DuplicateArgument { arg, token, position } => {
+                write!(f, "'{arg}' given more than once ('{token}' at position {position})")
+            }
+            ParseError::MutuallyExclusive { first, second, position } => write!(
+                f,
+                "'{second}' (at position {position}) cannot be used together with '{first}'"
+            ),
+            ParseError::MissingOneOf { args } => {
+                write!(f, "One of '{}' is necessary", args.join("', '"))
+            }
+            ParseError::InvalidUtf8 { token, position } => {
+                write!(f, "Argument '{token}' at position {position} is not valid UTF-8")
+            }
+            ParseError::UnterminatedQuote { quote, offset } => {
+                write!(f, "Unterminated quote ({quote}) opened at offset {offset}")
+            }
+            ParseError::HelpRequested => write!(f, "Help requested"),
+            ParseError::Multiple(errors) => {
+                write!(f, "{} errors found:", errors.len())?;
+                for e in errors {
+                    write!(f, "\n  - {e}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
@@ -0,0 +1,253 @@
+//! Usage and help text generation.
+//!
+//! The help is built from the registered arguments: their identifiers,
+//! metavar, number of values, default value and the `Help` option.
+use std::env;
+use std::path::Path;
+
+use crate::{Argument, ArgumentOption, ArgumentParser, ArgumentType, NArgs, OutputKind};
+
+const HELP_IDENTIFIERS: [&str; 2] = ["-h", "--help"];
+const HELP_TEXT: &str = "Show this help message and exit";
+const MAX_COLUMN: usize = 30;
+
+impl ArgumentParser {
+    /* Help identifiers not taken by a user argument */
+    pub(crate) fn help_identifiers(&self) -> Vec<&'static str> {
+        if !self.add_help {
+            return vec![];
+        }
+        HELP_IDENTIFIERS
+            .iter()
+            .filter(|id| !self.arguments.iter().any(|a| a.has_identifier(id)))
+            .copied()
+            .collect()
+    }
+
+    pub(crate) fn program_name(&self) -> String {
+        if let Some(name) = &self.program_name {
+            return name.clone();
+        }
+        env::args()
+            .next()
+            .and_then(|p| Path::new(&p).file_name().map(|f| f.to_string_lossy().into_owned()))
+            .unwrap_or_else(|| "program".to_owned())
+    }
+
+    fn values_str(argument: &Argument) -> String {
+        let m = argument.get_metavar();
+        match argument.n_args {
+            NArgs::Exact(n) => vec![m; n].join(" "),
+            NArgs::Optional => format!("[{m}]"),
+            NArgs::ZeroOrMore => format!("[{m} ...]"),
+            NArgs::OneOrMore => format!("{m} [{m} ...]"),
+            NArgs::Range(min, max) => format!("{m}{{{min},{max}}}"),
+        }
+    }
+
+    /* Identifiers shown in the help: --color and --no-color are --[no-]color */
+    fn identifiers_str(argument: &Argument) -> Vec<String> {
+        argument
+            .cl_identifiers
+            .iter()
+            .filter(|id| !argument.is_negation(id))
+            .map(|id| match id.strip_prefix("--") {
+                Some(name) if argument.has_option(ArgumentOption::Negatable) => format!("--[no-]{name}"),
+                _ => id.clone(),
+            })
+            .collect()
+    }
+
+    /* Usage of an argument, without the brackets of the non necessary ones */
+    fn usage_item(argument: &Argument) -> String {
+        let id = &ArgumentParser::identifiers_str(argument)[0];
+        match argument.get_type() {
+            ArgumentType::Positional => ArgumentParser::values_str(argument),
+            ArgumentType::Flag => id.clone(),
+            ArgumentType::Optional => format!("{id} {}", ArgumentParser::values_str(argument)),
+        }
+    }
+
+    fn usage_str(argument: &Argument) -> String {
+        let usage = ArgumentParser::usage_item(argument);
+        match argument.get_type() {
+            ArgumentType::Optional if argument.has_option(ArgumentOption::Necessary) => usage,
+            ArgumentType::Positional => usage,
+            _ => format!("[{usage}]"),
+        }
+    }
+
+    /* [--json | --yaml], (--json | --yaml) when one is necessary */
+    fn group_usage_str(&self, names: &[String], necessary: bool) -> String {
+        let items: Vec<String> = names
+            .iter()
+            .filter_map(|n| self.arguments.iter().find(|a| &a.name == n))
+            .map(ArgumentParser::usage_item)
+            .collect();
+        if necessary {
+            format!("({})", items.join(" | "))
+        } else {
+            format!("[{}]", items.join(" | "))
+        }
+    }
+
+    fn row_str(argument: &Argument) -> (String, String) {
+        let left = match argument.get_type() {
+            ArgumentType::Positional => ArgumentParser::values_str(argument),
+            ArgumentType::Flag => ArgumentParser::identifiers_str(argument).join(", "),
+            ArgumentType::Optional => format!(
+                "{} {}",
+                argument.cl_identifiers.join(", "),
+                ArgumentParser::values_str(argument)
+            ),
+        };
+
+        let mut right: Vec<String> = vec![];
+        if let Some(h) = argument.get_help() {
+            right.push(h);
+        }
+        if argument.get_type() != ArgumentType::Positional
+            && argument.has_option(ArgumentOption::Necessary)
+        {
+            right.push("[necessary]".to_owned());
+        }
+        if let Some(e) = argument.get_env() {
+            right.push(format!("[env: {e}]"));
+        }
+        if argument.get_type() != ArgumentType::Flag {
+            if let Some(d) = argument.get_default() {
+                right.push(format!("[default: {}]", d.get_value_str()));
+            }
+        }
+        (left, right.join(" "))
+    }
+
+    fn format_section(title: &str, rows: &[(String, String)]) -> String {
+        let width = rows
+            .iter()
+            .map(|(l, _)| l.len())
+            .filter(|l| *l <= MAX_COLUMN)
+            .max()
+            .unwrap_or(0);
+
+        let mut text = format!("{title}:\n");
+        for (left, right) in rows {
+            if right.is_empty() {
+                text.push_str(&format!("  {left}\n"));
+            } else if left.len() > MAX_COLUMN {
+                text.push_str(&format!("  {left}\n  {:width$}  {right}\n", ""));
+            } else {
+                text.push_str(&format!("  {left:width$}  {right}\n"));
+            }
+        }
+        text
+    }
+
+    fn declared_before(&self, name: &str, other: &str) -> bool {
+        let index = |n: &str| self.arguments.iter().position(|a| a.name == n);
+        index(name) < index(other)
+    }
+
+    pub fn format_usage(&self) -> String {
+        self.usage_for(&self.program_name())
+    }
+
+    pub fn format_help(&self) -> String {
+        self.help_for(&self.program_name())
+    }
+
+    pub fn print_help(&self) {
+        self.reporter
+            .write(OutputKind::Help, self.format_help().trim_end());
+    }
+
+    /* A subcommand is shown as "program subcommand" */
+    fn usage_for(&self, program_name: &str) -> String {
+        let mut usage = format!("Usage: {program_name}");
+        if let Some(h) = self.help_identifiers().first() {
+            usage.push_str(&format!(" [{h}]"));
+        }
+        /* Options first, then positionals in order. A mutually exclusive
+           group is shown where its first argument is. */
+        for a in self.arguments.iter().filter(|a| a.get_type() != ArgumentType::Positional) {
+            let group = self.exclusive_groups.iter().find(|(g, _)| g.contains(&a.name));
+            let item = match group {
+                Some((names, _)) if names.iter().any(|n| self.declared_before(n, &a.name)) => continue,
+                Some((names, necessary)) => self.group_usage_str(names, *necessary),
+                None => ArgumentParser::usage_str(a),
+            };
+            usage.push(' ');
+            usage.push_str(&item);
+        }
+        let (trailing, positionals): (Vec<&Argument>, Vec<&Argument>) = self
+            .arguments
+            .iter()
+            .filter(|a| a.get_type() == ArgumentType::Positional)
+            .partition(|a| a.has_option(ArgumentOption::Trailing));
+        for a in positionals {
+            usage.push(' ');
+            usage.push_str(&ArgumentParser::usage_str(a));
+        }
+        if !self.subcommands.is_empty() {
+            let names: Vec<&str> = self.subcommands.iter().map(|(n, _)| n.as_str()).collect();
+            usage.push_str(&format!(" {{{}}} ...", names.join(",")));
+        }
+        for a in trailing {
+            usage.push_str(&format!(" [-- {}]", ArgumentParser::values_str(a)));
+        }
+        usage
+    }
+
+    pub(crate) fn help_for(&self, program_name: &str) -> String {
+        let mut help = self.usage_for(program_name);
+        help.push('\n');
+        if let Some(d) = &self.description {
+            help.push_str(&format!("\n{d}\n"));
+        }
+
+        let rows = |t: ArgumentType| -> Vec<(String, String)> {
+            self.arguments
+                .iter()
+                .filter(|a| a.get_type() == t)
+                .map(ArgumentParser::row_str)
+                .collect()
+        };
+
+        let positionals = rows(ArgumentType::Positional);
+        if !positionals.is_empty() {
+            help.push('\n');
+            help.push_str(&ArgumentParser::format_section("Positional arguments", &positionals));
+        }
+
+        let mut optionals = rows(ArgumentType::Optional);
+        let help_ids = self.help_identifiers();
+        if !help_ids.is_empty() {
+            optionals.insert(0, (help_ids.join(", "), HELP_TEXT.to_owned()));
+        }
+        if !optionals.is_empty() {
+            help.push('\n');
+            help.push_str(&ArgumentParser::format_section("Optional arguments", &optionals));
+        }
+
+        let flags = rows(ArgumentType::Flag);
+        if !flags.is_empty() {
+            help.push('\n');
+            help.push_str(&ArgumentParser::format_section("Flags", &flags));
+        }
+
+        if !self.subcommands.is_empty() {
+            let subcommands: Vec<(String, String)> = self
+                .subcommands
+                .iter()
+                .map(|(n, p)| (n.clone(), p.description.clone().unwrap_or_default()))
+                .collect();
+            help.push('\n');
+            help.push_str(&ArgumentParser::format_section("Subcommands", &subcommands));
+        }
+
+        if let Some(e) = &self.epilog {
+            help.push_str(&format!("\n{e}\n"));
+        }
+        help
+    }
+}
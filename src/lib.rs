@@ -1,26 +1,189 @@
+use std::env;
+use std::ffi::OsString;
+use std::path::Path;
+use std::sync::Arc;
+
 use migformatting::Formatting;
-use std::{env};
+
+use config::ConfigFile;
+use response::{expand_response_files, Origin};
 
 mod argument;
-use argument::Content;
-pub use argument::{Argument, ArgumentOption, ArgumentType, DataType, ExtractFromContents, ListType, ContentList};
+mod config;
+mod derive;
+mod error;
+mod help;
+mod output;
+mod parsed;
+mod response;
+mod tokenizer;
+pub use argument::{Arg, Argument, ArgumentOption, Content, ArgumentType, DataType, ExtractFromContents, ListType, ContentList, DuplicatePolicy, NArgs};
+pub use derive::MigParser;
+pub use error::ParseError;
+pub use output::{OutputKind, Reporter, StdReporter};
+pub use parsed::{ParsedArgs, ValueSource};
+pub use tokenizer::split_command_line;
+
+#[cfg(feature = "derive")]
+pub use migparser_derive::MigParser;
 
 #[derive(Clone)]
 pub struct ArgumentParser {
     arguments: Vec<Argument>,
-    positional_cursor: i32,
+    collect_errors: bool,
+    add_help: bool,
+    program_name: Option<String>,
+    description: Option<String>,
+    epilog: Option<String>,
+    subcommands: Vec<(String, ArgumentParser)>,
+    last_parsed: Option<ParsedArgs>,
+    reporter: Arc<dyn Reporter>,
+    verbose: bool,
+    duplicates: DuplicatePolicy,
+    strict: bool,
+    config_file: Option<String>,
+    /* Name of the argument giving the configuration file */
+    config_argument: Option<String>,
+    response_files: bool,
+    /* Names of mutually exclusive arguments, and whether one is necessary */
+    exclusive_groups: Vec<(Vec<String>, bool)>,
+}
+
+/* Settings of the top-level parser that also apply to its subcommands */
+struct ParseContext<'a> {
+    program_name: String,
+    collect_errors: bool,
+    reporter: &'a dyn Reporter,
+    verbose: bool,
+    /* argv as given, for the arguments after `--` */
+    raw_arguments: &'a [OsString],
+    /* Unconsumed tokens are kept in the result instead of being errors */
+    keep_unknown: bool,
+}
+
+/* One occurrence of an argument on the command line */
+#[derive(Clone)]
+struct Occurrence {
+    /* argv index of the identifier (of the first value for positionals) */
+    position: usize,
+    /* Values with their argv index */
+    values: Vec<(usize, String)>,
 }
 
+/* Arguments named by one token, with their attached value */
+type Found = Vec<(usize, Option<String>)>;
+
+/* Arguments found on the command line, the argv index of the tokens
+   nobody took and of those after `--` */
+struct Scan {
+    occurrences: Vec<Vec<Occurrence>>,
+    leftovers: Vec<usize>,
+    trailing: Vec<usize>,
+    /* -h/--help was found (the scan stops there) */
+    help: bool,
+}
+
+impl ParseContext<'_> {
+    fn trace<F: FnOnce() -> String>(&self, message: F) {
+        if self.verbose {
+            self.reporter.write(OutputKind::Trace, &message());
+        }
+    }
+}
+
+/* A parser is a read-only spec while parsing: it can be shared between threads */
+const _: fn() = || {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<ArgumentParser>();
+};
+
 impl ArgumentParser {
     /* ++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++ */
     /* Creation +++++++++++++++++++++++++++++++++++++++++++++++++++++++++ */
     pub fn new() -> ArgumentParser {
         ArgumentParser {
             arguments: vec![],
-            positional_cursor: 1,
+            collect_errors: false,
+            add_help: true,
+            program_name: None,
+            description: None,
+            epilog: None,
+            subcommands: vec![],
+            last_parsed: None,
+            reporter: Arc::new(StdReporter),
+            verbose: false,
+            duplicates: DuplicatePolicy::FirstWins,
+            strict: true,
+            config_file: None,
+            config_argument: None,
+            response_files: false,
+            exclusive_groups: vec![],
         }
     }
 
+    /* Where help, errors, warnings and data are printed (StdReporter by default) */
+    pub fn set_reporter<R: Reporter + 'static>(&mut self, reporter: R) {
+        self.reporter = Arc::new(reporter);
+    }
+
+    /* Traces every parsing step through the reporter */
+    pub fn set_verbose(&mut self, verbose: bool) {
+        self.verbose = verbose;
+    }
+
+    /* Name shown in the usage line, argv[0] by default */
+    pub fn set_program_name(&mut self, name: &str) {
+        self.program_name = Some(name.to_owned());
+    }
+
+    /* Text shown between the usage line and the arguments in the help */
+    pub fn set_description(&mut self, description: &str) {
+        self.description = Some(description.to_owned());
+    }
+
+    /* Text shown at the end of the help */
+    pub fn set_epilog(&mut self, epilog: &str) {
+        self.epilog = Some(epilog.to_owned());
+    }
+
+    /* Whether -h/--help are registered automatically (true by default) */
+    pub fn set_add_help(&mut self, add_help: bool) {
+        self.add_help = add_help;
+    }
+
+    /* When set, parsing walks every argument and reports all the problems
+       found as a single ParseError::Multiple instead of stopping at the first. */
+    pub fn set_collect_errors(&mut self, collect: bool) {
+        self.collect_errors = collect;
+    }
+
+    /* What a flag or single-valued optional given several times keeps
+       (FirstWins by default), unless the argument sets its own. */
+    pub fn set_duplicate_policy(&mut self, policy: DuplicatePolicy) {
+        self.duplicates = policy;
+    }
+
+    /* When set (the default), every token no argument takes is an error.
+       Otherwise only the unknown option-like ones are, the others being
+       ignored with a warning. */
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+    }
+
+    /* Configuration file read, when it exists, for the arguments absent from
+       the command line and the environment */
+    pub fn set_config_file(&mut self, path: &str) {
+        self.config_file = Some(path.to_owned());
+    }
+
+    /* When set, @path arguments are replaced by the arguments listed in the
+       file before parsing. Errors about a token read from a file are wrapped
+       in ParseError::InResponseFile, giving the file and line (argv positions
+       are those of the expanded command line). */
+    pub fn set_response_files(&mut self, enable: bool) {
+        self.response_files = enable;
+    }
+
     /* ++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++ */
     /* Arguments ++++++++++++++++++++++++++++++++++++++++++++++++++++++++ */
     fn add_flag(
@@ -41,14 +204,14 @@ impl ArgumentParser {
         identifiers: Vec<String>,
         data_type: DataType,
         options: Option<Vec<ArgumentOption>>,
-        index: i32,
+        default_val: Option<Content>,
     ) -> Result<(), String> {
         self.arguments.push(Argument::new_positional(
             &name,
             identifiers,
             data_type,
             options,
-            index,
+            default_val,
         ));
         Ok(())
     }
@@ -60,7 +223,7 @@ impl ArgumentParser {
         data_type: DataType,
         options: Option<Vec<ArgumentOption>>,
         default_val: Option<Content>,
-        n_args_: usize
+        n_args_: NArgs
     ) -> Result<(), String> {
         self.arguments.push(Argument::new_optional(
             &name,
@@ -75,180 +238,651 @@ impl ArgumentParser {
 
     /* ++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++ */
     /* Parsing aux. +++++++++++++++++++++++++++++++++++++++++++++++++++++ */
-    fn parse_text<T: std::str::FromStr>(text: &String) -> Option<T> {
-        let parsed = text.parse::<T>().ok();
-        match parsed {
-            Some(c) => Some(c),
-            None => None,
+    fn parse_text<T: std::str::FromStr>(text: &str) -> Option<T> {
+        text.parse::<T>().ok()
+    }
+
+    fn parse_value(text: &str, type_: &DataType) -> Option<Content> {
+        match type_ {
+            DataType::Int => ArgumentParser::parse_text::<i32>(text).map(Content::Int),
+            DataType::Uint => ArgumentParser::parse_text::<u32>(text).map(Content::Uint),
+            DataType::Bool => ArgumentParser::parse_text::<bool>(text).map(Content::Bool),
+            DataType::String => Some(Content::String(text.to_owned())),
+            DataType::Float => ArgumentParser::parse_text::<f32>(text).map(Content::Float),
+            DataType::List(t) => {
+                let mut result = ContentList::new(t.clone());
+                for v in text.split_whitespace() {
+                    result
+                        .data
+                        .push(ArgumentParser::parse_value(v, &t.data_type())?);
+                }
+                Some(Content::List(result))
+            }
         }
     }
 
-    fn parse_value(text: &String, type_: &DataType) -> Option<Content> {
-        let res: Option<Content> = match type_ {
-            DataType::Int => {
-                let parsed = ArgumentParser::parse_text::<i32>(text);
-                if let Some(c) = parsed {
-                    return Some(Content::Int(c));
-                } else {
-                    return None;
+    /* Parses the values of one occurrence, given with their argv index so
+       errors can point at the offending token. Without values, lists are
+       empty and other types keep their default. */
+    fn parse_values(
+        argument: &Argument,
+        values: &[(usize, String)],
+    ) -> Result<Option<Content>, ParseError> {
+        let invalid = |raw: &String, expected: DataType, position: usize| ParseError::InvalidValue {
+            arg: argument.name.clone(),
+            raw: raw.clone(),
+            expected,
+            position,
+        };
+
+        match &argument.data_type {
+            DataType::List(t) => {
+                let item_type = t.data_type();
+                let mut result = ContentList::new(t.clone());
+                for (position, v) in values {
+                    let item = ArgumentParser::parse_value(v, &item_type)
+                        .ok_or_else(|| invalid(v, item_type.clone(), *position))?;
+                    result.data.push(item);
                 }
+                Ok(Some(Content::List(result)))
             }
-            DataType::Uint => {
-                let parsed = ArgumentParser::parse_text::<u32>(text);
-                if let Some(c) = parsed {
-                    return Some(Content::Uint(c));
-                } else {
-                    return None;
+            type_ => match values.first() {
+                Some((position, v)) => ArgumentParser::parse_value(v, type_)
+                    .map(Some)
+                    .ok_or_else(|| invalid(v, type_.clone(), *position)),
+                None => Ok(argument.get_default()),
+            },
+        }
+    }
+
+    /* Index of the optional or flag having this identifier */
+    fn find_identifier(&self, token: &str) -> Option<usize> {
+        self.arguments
+            .iter()
+            .position(|a| a.get_type() != ArgumentType::Positional && a.has_identifier(token))
+    }
+
+    /* Reads `-abc` as bundled short identifiers (-a -b -c). The first one
+       taking values gets the rest of the token as attached value (-n5). The
+       bool tells whether the help (-vh) ends the bundle. */
+    fn split_bundle(&self, token: &str) -> Option<(Found, bool)> {
+        if token.len() < 3 || !token.starts_with('-') || token.starts_with("--") {
+            return None;
+        }
+        let help_ids = self.help_identifiers();
+        let mut bundle = vec![];
+        for (k, c) in token[1..].char_indices() {
+            let identifier = format!("-{c}");
+            if help_ids.contains(&identifier.as_str()) {
+                return Some((bundle, true));
+            }
+            let ix = self.find_identifier(&identifier)?;
+            match self.arguments[ix].get_type() {
+                ArgumentType::Optional => {
+                    let rest = &token[1 + k + c.len_utf8()..];
+                    bundle.push((ix, Some(rest.to_owned()).filter(|r| !r.is_empty())));
+                    return Some((bundle, false));
                 }
+                _ => bundle.push((ix, None)),
             }
-            DataType::Bool => {
-                let parsed = ArgumentParser::parse_text::<bool>(text);
-                if let Some(c) = parsed {
-                    return Some(Content::Bool(c));
-                } else {
-                    return None;
+        }
+        Some((bundle, false))
+    }
+
+    /* Records the occurrence of argument `ix` found at argv index `i`, taking
+       its values from `attached` and the following tokens. Returns the index
+       of the next token to scan, also on error so that the scan can go on. */
+    fn consume(
+        &self,
+        ix: usize,
+        i: usize,
+        attached: Option<String>,
+        arguments: &[String],
+        scan: &mut Scan,
+    ) -> Result<usize, (ParseError, usize)> {
+        let argument = &self.arguments[ix];
+        if argument.get_type() == ArgumentType::Flag {
+            if attached.is_some() {
+                let e = ParseError::UnexpectedValue {
+                    arg: argument.name.clone(),
+                    token: arguments[i].clone(),
+                    position: i,
+                };
+                return Err((e, i + 1));
+            }
+            scan.occurrences[ix].push(Occurrence { position: i, values: vec![] });
+            return Ok(i + 1);
+        }
+
+        let n_args = argument.n_args;
+        let mut values: Vec<(usize, String)> = attached.into_iter().map(|v| (i, v)).collect();
+        /* An attached value ends the occurrence unless more are needed */
+        let max = if values.is_empty() {
+            n_args.max()
+        } else {
+            Some(n_args.min().max(1))
+        };
+        /* A fixed number of values may start with a dash (-5, -x), but
+           stops at `--` and at the identifiers of the parser; a variable
+           one is taken greedily until anything looking like an identifier */
+        let fixed = n_args.max() == Some(n_args.min());
+        let stops = |token: &str| if fixed { self.is_identifier(token) } else { self.ends_values(token) };
+        let mut next = i + 1;
+        while max.is_none_or(|m| values.len() < m) && next < arguments.len() && !stops(&arguments[next]) {
+            values.push((next, arguments[next].clone()));
+            next += 1;
+        }
+        if values.len() < n_args.min() {
+            let e = ParseError::NotEnoughValues {
+                arg: argument.name.clone(),
+                token: arguments[i].clone(),
+                position: i,
+                expected: n_args.min(),
+                found: values.len(),
+            };
+            return Err((e, next));
+        }
+        scan.occurrences[ix].push(Occurrence { position: i, values });
+        Ok(next)
+    }
+
+    /* Whether a token stops the values of an optional */
+    fn ends_values(&self, token: &str) -> bool {
+        Argument::looks_like_identifier(token) || self.find_identifier(token).is_some()
+    }
+
+    /* Whether a token is `--` or names an optional or flag (--x=v included) */
+    fn is_identifier(&self, token: &str) -> bool {
+        let name = match token.split_once('=') {
+            Some((name, _)) if token.starts_with("--") => name,
+            _ => token,
+        };
+        token == "--" || self.find_identifier(name).is_some()
+    }
+
+    /* First pass: finds every optional and flag, then gives the other
+       tokens to the positionals */
+    fn scan_options(
+        &self,
+        arguments: &[String],
+        context: &ParseContext,
+        errors: &mut Vec<ParseError>,
+    ) -> Result<Scan, ParseError> {
+        let mut scan = Scan {
+            occurrences: vec![vec![]; self.arguments.len()],
+            leftovers: vec![],
+            trailing: vec![],
+            help: false,
+        };
+        let help_ids = self.help_identifiers();
+        let mut i = 1;
+        while i < arguments.len() {
+            let token = &arguments[i];
+            /* `--` ends the options */
+            if token == "--" && self.find_identifier(token).is_none() {
+                scan.trailing = (i + 1..arguments.len()).collect();
+                break;
+            }
+            /* Only where an identifier is expected: `--name -h` gives a value */
+            if help_ids.contains(&token.as_str()) {
+                scan.help = true;
+                break;
+            }
+            let mut found: Found = vec![];
+            if let Some(ix) = self.find_identifier(token) {
+                found.push((ix, None));
+            } else if let Some((name, value)) = token.split_once('=').filter(|_| token.starts_with("--")) {
+                if let Some(ix) = self.find_identifier(name) {
+                    found.push((ix, Some(value.to_owned())));
+                }
+            } else if let Some((bundle, help)) = self.split_bundle(token) {
+                if help {
+                    scan.help = true;
+                    break;
                 }
+                found = bundle;
             }
-            DataType::String => Some(Content::String(text.clone())),
-            DataType::Float => {
-                let parsed = ArgumentParser::parse_text::<f32>(text);
-                if let Some(c) = parsed {
-                    return Some(Content::Float(c));
-                } else {
-                    return None;
+
+            if found.is_empty() {
+                scan.leftovers.push(i);
+                i += 1;
+                continue;
+            }
+            let mut next = i + 1;
+            for (ix, attached) in found {
+                match self.consume(ix, i, attached, arguments, &mut scan) {
+                    Ok(n) => next = next.max(n),
+                    Err((e, n)) => {
+                        ArgumentParser::push_error(context.collect_errors, errors, e)?;
+                        next = next.max(n);
+                    }
                 }
+            }
+            i = next;
+        }
+        self.allocate_positionals(arguments, &mut scan);
+        Ok(scan)
+    }
+
+    /* Shares the tokens left by the optionals and flags between the
+       positionals, in declaration order and wherever they are in the command
+       line: each one gets its minimum, and the variable ones take what the
+       following ones do not need (cp SRC... DST). The tokens after `--` go
+       to the Trailing positional if any, else to the positionals as well. */
+    fn allocate_positionals(&self, arguments: &[String], scan: &mut Scan) {
+        let positional = |ix: &usize| self.arguments[*ix].get_type() == ArgumentType::Positional;
+        let trailing_ix = (0..self.arguments.len())
+            .filter(positional)
+            .find(|ix| self.arguments[*ix].has_option(ArgumentOption::Trailing));
+        let positionals: Vec<usize> = (0..self.arguments.len())
+            .filter(positional)
+            .filter(|ix| Some(*ix) != trailing_ix)
+            .collect();
+
+        let mut tokens: Vec<usize> = scan
+            .leftovers
+            .iter()
+            .copied()
+            .filter(|i| !Argument::looks_like_identifier(&arguments[*i]))
+            .collect();
+        match trailing_ix {
+            Some(ix) if !scan.trailing.is_empty() => {
+                let values = scan.trailing.iter().map(|i| (*i, arguments[*i].clone())).collect();
+                scan.occurrences[ix].push(Occurrence { position: scan.trailing[0], values });
+            }
+            Some(_) => {}
+            None => tokens.extend(&scan.trailing),
+        }
+
+        let mut taken = 0;
+        for (k, ix) in positionals.iter().enumerate() {
+            let n_args = self.arguments[*ix].n_args;
+            let available = tokens.len() - taken;
+            let needed_after: usize = positionals[k + 1..]
+                .iter()
+                .map(|p| self.arguments[*p].n_args.min())
+                .sum();
+            let wanted = available
+                .saturating_sub(needed_after)
+                .max(n_args.min().min(available));
+            let count = n_args.max().map_or(wanted, |m| wanted.min(m));
+            if count == 0 {
+                continue;
+            }
+            let values = tokens[taken..taken + count]
+                .iter()
+                .map(|i| (*i, arguments[*i].clone()))
+                .collect();
+            scan.occurrences[*ix].push(Occurrence { position: tokens[taken], values });
+            taken += count;
+        }
+        scan.leftovers.retain(|i| !tokens[..taken].contains(i));
+        /* The extra tokens after `--` are left over too, unless the Trailing
+           positional took them all */
+        if trailing_ix.is_none() {
+            let untaken = scan.trailing.iter().filter(|i| !tokens[..taken].contains(i));
+            scan.leftovers.extend(untaken);
+        }
+    }
+
+    /* Second pass: value of one argument from what the scan found, and
+       where it comes from */
+    fn resolve_arg(
+        &self,
+        ix: usize,
+        arguments: &[String],
+        scan: &Scan,
+        config: Option<&ConfigFile>,
+    ) -> Result<Option<(Content, ValueSource)>, ParseError> {
+        let argument = &self.arguments[ix];
+        let occurrences = &scan.occurrences[ix];
+        /* The occurrence giving the value, and the value */
+        let given: Option<(usize, Option<Content>)> = match argument.get_type() {
+            ArgumentType::Flag if argument.has_option(ArgumentOption::Count) => occurrences
+                .first()
+                .map(|o| (o.position, Some(Content::Uint(occurrences.len() as u32)))),
+            ArgumentType::Flag => self.pick_occurrence(argument, occurrences, arguments)?.map(|o| {
+                let negated = argument.is_negation(&arguments[o.position]);
+                let value = Content::Bool(argument.has_option(ArgumentOption::StoreTrue) != negated);
+                (o.position, Some(value))
+            }),
+            /* Every occurrence adds its values, in order */
+            ArgumentType::Optional
+                if argument.has_option(ArgumentOption::Append)
+                    || argument.has_option(ArgumentOption::Extend) =>
+            {
+                match occurrences.first() {
+                    Some(first) => {
+                        let values: Vec<(usize, String)> = occurrences
+                            .iter()
+                            .flat_map(|o| o.values.iter().cloned())
+                            .collect();
+                        Some((first.position, ArgumentParser::parse_values(argument, &values)?))
+                    }
+                    None => None,
+                }
+            }
+            ArgumentType::Optional => match self.pick_occurrence(argument, occurrences, arguments)? {
+                Some(o) => Some((o.position, ArgumentParser::parse_values(argument, &o.values)?)),
+                None => None,
             },
-            DataType::List(t) => {
-                let values_txt = text.trim().split(' ').collect::<Vec<&str>>();
-                let mut result = ContentList::new(t.clone());
-                match t {
-                    ListType::Bool => {
-                        for i in values_txt {
-                            let v = ArgumentParser::parse_text::<bool>(&i.to_owned()).unwrap();
-                            result.data.push(Content::Bool(v));
-                        }
-                    },
-                    ListType::Int => {
-                        for i in values_txt {
-                            let v = ArgumentParser::parse_text::<i32>(&i.to_owned()).unwrap();
-                            result.data.push(Content::Int(v));
-                        }
-                    },
-                    ListType::Uint => {
-                        for i in values_txt {
-                            let v = ArgumentParser::parse_text::<u32>(&i.to_owned()).unwrap();
-                            result.data.push(Content::Uint(v));
-                        }
-                    },
-                    ListType::String => {
-                        for i in values_txt {
-                            let v = ArgumentParser::parse_text::<String>(&i.to_owned()).unwrap();
-                            result.data.push(Content::String(v));
-                        }
-                    },
-                    ListType::Float => {
-                        for i in values_txt {
-                            let v = ArgumentParser::parse_text::<f32>(&i.to_owned()).unwrap();
-                            result.data.push(Content::Float(v));
-                        }
-                    },
+            ArgumentType::Positional => match occurrences.first() {
+                Some(o) if o.values.len() < argument.n_args.min() => {
+                    let (position, token) = o.values.last().unwrap().clone();
+                    return Err(ParseError::NotEnoughValues {
+                        arg: argument.name.clone(),
+                        token,
+                        position,
+                        expected: argument.n_args.min(),
+                        found: o.values.len(),
+                    });
                 }
-                Some(Content::List(result))
+                Some(o) => Some((o.position, ArgumentParser::parse_values(argument, &o.values)?)),
+                None => None,
+            },
+        };
+
+        /* Command line > environment > configuration file > default. An
+           optional given without its optional value (NArgs::Optional) has
+           its default, if any, but never one from elsewhere. */
+        let data = match given {
+            Some((index, data)) => return Ok(data.map(|d| (d, ValueSource::CommandLine { index }))),
+            None => ArgumentParser::env_value(argument)?.map(|d| (d, ValueSource::Environment)),
+        };
+        let data = match (data, config) {
+            (Some(d), _) => Some(d),
+            (None, Some(c)) => c.content(argument)?.map(|d| {
+                (d, ValueSource::ConfigFile { path: c.path().to_owned() })
+            }),
+            (None, None) => None,
+        };
+        if data.is_none() && argument.has_option(ArgumentOption::Necessary) {
+            return Err(ParseError::MissingRequired { arg: argument.name.clone() });
+        }
+        Ok(data)
+    }
+
+    /* The configuration file named by its argument (`given`, from the command
+       line or the environment), else the one set with set_config_file if it
+       exists */
+    fn load_config(&self, given: Option<&Content>) -> Result<Option<ConfigFile>, ParseError> {
+        match (given, &self.config_file) {
+            (Some(Content::String(path)), _) => ConfigFile::load(path).map(Some),
+            (_, Some(path)) if Path::new(path).exists() => ConfigFile::load(path).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    /* Value of the environment variable of an argument, if set */
+    fn env_value(argument: &Argument) -> Result<Option<Content>, ParseError> {
+        let Some(var) = argument.get_env() else { return Ok(None) };
+        let Some(raw) = env::var_os(&var) else { return Ok(None) };
+        let invalid = |raw: String| ParseError::InvalidEnvValue {
+            arg: argument.name.clone(),
+            var: var.clone(),
+            raw,
+            expected: argument.data_type.clone(),
+        };
+        let raw = raw
+            .into_string()
+            .map_err(|r| invalid(r.to_string_lossy().into_owned()))?;
+        match ArgumentParser::parse_value(&raw, &argument.data_type) {
+            Some(d) => Ok(Some(d)),
+            None => Err(invalid(raw)),
+        }
+    }
+
+    /* The occurrence giving the value of a flag or optional given several
+       times, following its duplicate policy or the parser one */
+    fn pick_occurrence<'o>(
+        &self,
+        argument: &Argument,
+        occurrences: &'o [Occurrence],
+        arguments: &[String],
+    ) -> Result<Option<&'o Occurrence>, ParseError> {
+        /* --color --no-color: the last one is meant */
+        let default = if argument.has_option(ArgumentOption::Negatable) {
+            DuplicatePolicy::LastWins
+        } else {
+            self.duplicates
+        };
+        match argument.get_duplicates().unwrap_or(default) {
+            DuplicatePolicy::FirstWins => Ok(occurrences.first()),
+            DuplicatePolicy::LastWins => Ok(occurrences.last()),
+            DuplicatePolicy::Error => match occurrences {
+                [_, again, ..] => Err(ParseError::DuplicateArgument {
+                    arg: argument.name.clone(),
+                    token: arguments[again.position].clone(),
+                    position: again.position,
+                }),
+                _ => Ok(occurrences.first()),
+            },
+        }
+    }
+
+    /* At most one argument of each group on the command line, and one
+       having a value (from anywhere) in the necessary groups */
+    fn check_exclusive_groups(&self, scan: &Scan, parsed: &ParsedArgs) -> Vec<ParseError> {
+        let mut errors = vec![];
+        for (names, necessary) in &self.exclusive_groups {
+            let mut given: Vec<(usize, &String)> = names
+                .iter()
+                .filter_map(|name| {
+                    let ix = self.arguments.iter().position(|a| &a.name == name)?;
+                    scan.occurrences[ix].first().map(|o| (o.position, name))
+                })
+                .collect();
+            given.sort();
+            if let [(_, first), (position, second), ..] = given[..] {
+                errors.push(ParseError::MutuallyExclusive {
+                    first: first.clone(),
+                    second: second.clone(),
+                    position,
+                });
+            }
+            let has_value = |name: &String| {
+                !matches!(parsed.value_source(name), None | Some(ValueSource::Default))
+            };
+            if *necessary && !names.iter().any(has_value) {
+                errors.push(ParseError::MissingOneOf { args: names.clone() });
+            }
+        }
+        errors
+    }
+
+    /* Records a parse error: stops the parsing unless errors are collected */
+    fn push_error(
+        collect_errors: bool,
+        errors: &mut Vec<ParseError>,
+        e: ParseError,
+    ) -> Result<(), ParseError> {
+        if !collect_errors {
+            return Err(e);
+        }
+        match e {
+            ParseError::Multiple(mut es) => errors.append(&mut es),
+            _ => errors.push(e),
+        }
+        Ok(())
+    }
+
+    /* Index of the token naming the subcommand, skipping option values */
+    fn find_subcommand(&self, arguments: &[String]) -> Option<usize> {
+        if self.subcommands.is_empty() {
+            return None;
+        }
+        let mut i = 1;
+        while i < arguments.len() {
+            let token = &arguments[i];
+            if token == "--" {
+                return None;
+            }
+            if self.subcommands.iter().any(|(name, _)| name == token) {
+                return Some(i);
             }
+            match self.arguments.iter().find(|a| a.has_identifier(token)) {
+                Some(a) if a.get_type() == ArgumentType::Optional => i += a.n_args.min() + 1,
+                _ => i += 1,
+            }
+        }
+        None
+    }
+
+    /* Whether -h/--help is given to this parser or to one of its
+       subcommands, without printing anything */
+    fn help_requested(&self, cl_arguments: &[String], context: &ParseContext) -> bool {
+        let split = self.find_subcommand(cl_arguments);
+        let arguments = &cl_arguments[..split.unwrap_or(cl_arguments.len())];
+        let context = ParseContext {
+            program_name: context.program_name.clone(),
+            collect_errors: true,
+            ..*context
         };
+        if self.scan_options(arguments, &context, &mut vec![]).is_ok_and(|s| s.help) {
+            return true;
+        }
+        split.is_some_and(|ix| {
+            let child = self.get_subcommand_parser(&cl_arguments[ix]).unwrap();
+            child.help_requested(&cl_arguments[ix..], &context)
+        })
+    }
 
-        res
+    /* Parses the part of the command line from the subcommand at `ix`, the
+       positions of its values and errors being those of this parser argv */
+    fn parse_subcommand(
+        &self,
+        cl_arguments: &[String],
+        ix: usize,
+        context: &ParseContext,
+    ) -> Result<ParsedArgs, ParseError> {
+        let name = &cl_arguments[ix];
+        let child = self.get_subcommand_parser(name).unwrap();
+        let child_context = ParseContext {
+            program_name: match &child.program_name {
+                Some(n) => n.clone(),
+                None => format!("{} {name}", context.program_name),
+            },
+            raw_arguments: &context.raw_arguments[ix..],
+            ..*context
+        };
+        context.trace(|| format!("  subcommand '{name}'"));
+        /* The child sees the subcommand name as its argv[0] */
+        child
+            .parse_cl_arguments(&cl_arguments[ix..], &child_context)
+            .map(|child_parsed| child_parsed.shifted(ix))
+            .map_err(|e| e.shifted(ix))
     }
 
-    fn parse_arg(
-        &mut self,
-        cl_arguments: &Vec<String>,
-        used_cl_args: &mut Vec<bool>,
-        argument_ix: usize,
-    ) {
-        /* Loop on cl arguments */
-        let mut argument = self.arguments[argument_ix].clone();
-        let arg_name = argument.name.clone();
-        let arg_type = argument.get_type();
-        let data_type = argument.data_type.clone();
-        let cl_n_args: usize = cl_arguments.len();
-        for (i, arg) in cl_arguments.iter().enumerate() {
-            if i != 0 {
-                match arg_type {
-                    ArgumentType::Flag => {
-                        if argument.has_identifier(arg) && !used_cl_args[i]
-                        {   
-                            if argument.has_option(ArgumentOption::StoreTrue) {
-                                argument.set_data(Content::Bool(true));
-                            } else {
-                                argument.set_data(Content::Bool(false));
-                            }
-                            argument.set_parsed();
-                        }
-                    }
-                    ArgumentType::Positional => {
-                        if i32::try_from(i).unwrap() == argument.get_index() && !used_cl_args[i] {
-                            let data = ArgumentParser::parse_value(
-                                &cl_arguments[i],
-                                &data_type,
-                            );
-                            if let Some(d) = data {
-                                argument.set_data(d);
-                            }
-                            argument.set_parsed();
-                        } else if i32::try_from(i).unwrap() > argument.get_index() {
-                            break;
-                        }
-                    }
-                    ArgumentType::Optional => {
-                        if argument.has_identifier(arg) && !used_cl_args[i]
-                        {
-                            let n_args = argument.n_args.clone();
-                            if i + n_args > cl_n_args {
-                                println!("Not enough arguments for list ({n_args},{cl_n_args})! ");
-                                panic!();
-                            }
-                            let data_args = cl_arguments[i + 1 .. i + argument.n_args + 1].to_vec();
-                            let mut data_txt: String = String::new();
-                            
-                            for i in data_args {
-                                data_txt.push_str(&i);
-                                data_txt.push(' ');
-                            }
-                            
-                            let data =
-                                ArgumentParser::parse_value(&data_txt, &data_type);
-                            if let Some(d) = data {
-                                argument.set_data(d);
-                            }
-                            argument.set_parsed();
-                            for j in 0..n_args {
-                                used_cl_args[i + j] = true;
-                            }
-                            
-                        }
+    fn parse_cl_arguments(
+        &self,
+        cl_arguments: &[String],
+        context: &ParseContext,
+    ) -> Result<ParsedArgs, ParseError> {
+        context.trace(|| format!("{}: parsing {cl_arguments:?}", context.program_name));
+        let collect_errors = context.collect_errors;
+
+        /* Everything after the subcommand belongs to its parser */
+        let split = self.find_subcommand(cl_arguments);
+        let arguments = &cl_arguments[..split.unwrap_or(cl_arguments.len())];
+
+        let mut parsed = ParsedArgs::new();
+        let mut errors: Vec<ParseError> = vec![];
+        let scan = self.scan_options(arguments, context, &mut errors)?;
+        if scan.help {
+            let help = self.help_for(&context.program_name);
+            context.reporter.write(OutputKind::Help, help.trim_end());
+            return Err(ParseError::HelpRequested);
+        }
+        /* The help of the subcommand is printed whatever this parser thinks
+           of its own arguments */
+        if let Some(ix) = split {
+            let child = self.get_subcommand_parser(&cl_arguments[ix]).unwrap();
+            if child.help_requested(&cl_arguments[ix..], context) {
+                self.parse_subcommand(cl_arguments, ix, context)?;
+            }
+        }
+        parsed.set_trailing(scan.trailing.iter().map(|i| context.raw_arguments[*i].clone()).collect());
+        /* The configuration file first, it gives values to the others. Its
+           argument is resolved only once, its errors being reported in order. */
+        let config_ix = self
+            .config_argument
+            .as_ref()
+            .and_then(|name| self.arguments.iter().position(|a| &a.name == name));
+        let mut config_arg = config_ix.map(|ix| self.resolve_arg(ix, arguments, &scan, None));
+        let given = match &config_arg {
+            Some(Ok(Some((path, _)))) => Some(path),
+            _ => None,
+        };
+        let config = match self.load_config(given) {
+            Ok(c) => c,
+            Err(e) => {
+                ArgumentParser::push_error(collect_errors, &mut errors, e)?;
+                None
+            }
+        };
+        for (arg_ix, argument) in self.arguments.iter().enumerate() {
+            parsed.set_occurrences(&argument.name, scan.occurrences[arg_ix].len());
+            let resolved = match config_arg.take_if(|_| Some(arg_ix) == config_ix) {
+                Some(r) => r,
+                None => self.resolve_arg(arg_ix, arguments, &scan, config.as_ref()),
+            };
+            match resolved {
+                Ok(Some((data, source))) => {
+                    context.trace(|| format!("  {} = {} ({source:?})", argument.name, data.get_value_str()));
+                    parsed.set_value(&argument.name, data, source);
+                }
+                Ok(None) => {
+                    if let Some(d) = argument.get_default() {
+                        context.trace(|| format!("  {} = {} (default)", argument.name, d.get_value_str()));
+                        parsed.set_value(&argument.name, d, ValueSource::Default);
                     }
                 }
-                if argument.is_parsed(){
-                    used_cl_args[i] = true;
-                    self.arguments[argument_ix] = argument.clone();
-                    break;
+                Err(e) => ArgumentParser::push_error(collect_errors, &mut errors, e)?,
+            }
+        }
+
+        for e in self.check_exclusive_groups(&scan, &parsed) {
+            ArgumentParser::push_error(collect_errors, &mut errors, e)?;
+        }
+
+        /* Tokens nobody took, only option-like ones (before `--`) outside
+           strict mode, the others being ignored with a warning */
+        if context.keep_unknown {
+            parsed.set_unknown(scan.leftovers.iter().map(|i| arguments[*i].clone()).collect());
+        } else {
+            for i in scan.leftovers {
+                let option_like = !scan.trailing.contains(&i) && Argument::looks_like_identifier(&arguments[i]);
+                if self.strict || option_like {
+                    let e = ParseError::UnknownArgument {
+                        token: arguments[i].clone(),
+                        position: i,
+                    };
+                    ArgumentParser::push_error(collect_errors, &mut errors, e)?;
+                } else {
+                    let warning = format!("warning: ignoring argument '{}' at position {i}", arguments[i]);
+                    context.reporter.write(OutputKind::Warning, &warning.warning());
                 }
             }
         }
 
-        if !argument.is_parsed() && argument.has_option(ArgumentOption::Necessary) {
-            println!(
-                "{}",
-                format!("Necessary argument '{}' is not present", arg_name).error()
-            );
-            panic!();
+        if let Some(ix) = split {
+            match self.parse_subcommand(cl_arguments, ix, context) {
+                Ok(child_parsed) => parsed.set_subcommand(&cl_arguments[ix], child_parsed),
+                Err(ParseError::HelpRequested) => return Err(ParseError::HelpRequested),
+                Err(e) => ArgumentParser::push_error(collect_errors, &mut errors, e)?,
+            }
+        }
+
+        match errors.len() {
+            0 => Ok(parsed),
+            1 => Err(errors.remove(0)),
+            _ => Err(ParseError::Multiple(errors)),
         }
     }
 
     /* ++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++ */
     /* User API +++++++++++++++++++++++++++++++++++++++++++++++++++++++++ */
-     pub fn add_argument(
+    pub fn add_argument(
         &mut self,
         name: &str,
         alias: Option<Vec<String>>,
@@ -256,12 +890,41 @@ impl ArgumentParser {
         options_: Option<Vec<ArgumentOption>>,
         default_value: Option<Content>,
     ) -> Result<(), String> {
+        let mut arg = Arg::new(name).data_type(data_type);
+        for a in alias.unwrap_or_default() {
+            arg = arg.alias(&a);
+        }
+        for o in options_.unwrap_or_default() {
+            arg = arg.option(o);
+        }
+        if let Some(d) = default_value {
+            arg = arg.default(d);
+        }
+        self.arg(arg)
+    }
+
+    pub fn arg(&mut self, arg: Arg) -> Result<(), String> {
+        let Arg {
+            name,
+            aliases,
+            data_type,
+            mut options,
+            default_value,
+        } = arg;
+        let name = name.as_str();
+
         /* Set-up*/
-        let mut data: Option<Content> = default_value.clone();
-        let mut options = options_.unwrap_or_default();
+        let mut data: Option<Content> = default_value;
 
+        /* Count */
+        if options.contains(&ArgumentOption::Count) {
+            if !name.starts_with('-') || data_type != DataType::Uint {
+                return Err(format!("Count arg '{name}' must be a Uint flag!"));
+            }
+            data = data.or(Some(Content::Uint(0)));
+        }
         /* Bool */
-        if data_type == DataType::Bool {
+        else if data_type == DataType::Bool {
             if options.contains(&ArgumentOption::StoreFalse) {
                 data = Some(Content::Bool(true));
             } else {
@@ -280,13 +943,47 @@ impl ArgumentParser {
         };
         let cl_name = name.to_owned(); // keeping --arg if present
         let mut identifiers = vec![cl_name.clone()];
-        if let Some(mut i) = alias {
-            identifiers.append(&mut i);
+        identifiers.extend(aliases);
+        if options.contains(&ArgumentOption::Negatable) {
+            let negations: Vec<String> = identifiers
+                .iter()
+                .filter_map(|id| id.strip_prefix("--").map(|name| format!("--no-{name}")))
+                .collect();
+            if data_type != DataType::Bool || negations.is_empty() {
+                return Err(format!("Negatable arg '{arg_name}' must be a flag with a --name!"));
+            }
+            identifiers.extend(negations);
         }
         let n_args = Argument::get_n_args(&options);
+        if n_args.max() == Some(0) || n_args.max().is_some_and(|m| m < n_args.min()) {
+            return Err(format!("Invalid number of values for arg '{arg_name}'!"));
+        }
+        if n_args.max() != Some(1)
+            && data_type != DataType::Bool
+            && !matches!(data_type, DataType::List(_))
+        {
+            return Err(format!("Arg '{arg_name}' takes several values: it needs a DataType::List!"));
+        }
 
         /* Positional - Optional - Flags */
         let argument_type = Argument::guess_type(name, &options, &data_type);
+        let append = options.contains(&ArgumentOption::Append);
+        if append || options.contains(&ArgumentOption::Extend) {
+            if argument_type != Some(ArgumentType::Optional) || !matches!(data_type, DataType::List(_)) {
+                return Err(format!("Repeated arg '{arg_name}' must be an optional DataType::List!"));
+            }
+            if append && n_args.max() != Some(1) {
+                return Err(format!("Appended arg '{arg_name}' takes one value, use Extend!"));
+            }
+        }
+        if options.contains(&ArgumentOption::Trailing) {
+            if argument_type != Some(ArgumentType::Positional) || !matches!(data_type, DataType::List(_)) {
+                return Err(format!("Trailing arg '{arg_name}' must be a positional DataType::List!"));
+            }
+            if self.arguments.iter().any(|a| a.has_option(ArgumentOption::Trailing)) {
+                return Err("Only one arg can take the trailing arguments!".to_owned());
+            }
+        }
         match argument_type {
             Some(t) => {
                 match t {
@@ -295,18 +992,10 @@ impl ArgumentParser {
                     }
                     ArgumentType::Positional => {
                         // Add the necesary option if not already
-                        if !options.contains(&ArgumentOption::Necessary) {
+                        if n_args.min() > 0 && !options.contains(&ArgumentOption::Necessary) {
                             options.push(ArgumentOption::Necessary);
                         }
-                        let index = self.positional_cursor;
-                        self.positional_cursor = self.positional_cursor + 1;
-                        self.add_positional(
-                            arg_name,
-                            identifiers,
-                            data_type,
-                            Some(options),
-                            index,
-                        )?;
+                        self.add_positional(arg_name, identifiers, data_type, Some(options), data)?;
                     }
                     ArgumentType::Flag => {
                         self.add_flag(arg_name, identifiers, Some(options), data)?;
@@ -320,50 +1009,213 @@ impl ArgumentParser {
         Ok(())
     }
 
-    pub fn parse_arguments(&mut self) {
-        let arguments: Vec<String> = env::args().collect();
-        println!("{}", format!("Arguments: \n {arguments:?}"));
-        let mut used_arguments: Vec<bool> = vec![false; arguments.len()];
-        for arg_ix in 0..self.arguments.len() { 
-            self.parse_arg(&arguments, &mut used_arguments, arg_ix);
+    fn parse_os(&self, raw_arguments: &[OsString], keep_unknown: bool) -> Result<ParsedArgs, ParseError> {
+        if !self.response_files {
+            return self.parse_expanded(raw_arguments, keep_unknown);
+        }
+        let (expanded, origins): (Vec<OsString>, Vec<Origin>) =
+            expand_response_files(raw_arguments)?.into_iter().unzip();
+        self.parse_expanded(&expanded, keep_unknown)
+            .map_err(|e| ArgumentParser::locate_error(e, &origins))
+    }
+
+    /* Points the errors about tokens read from a response file at the file
+       and line they come from */
+    fn locate_error(e: ParseError, origins: &[Origin]) -> ParseError {
+        if let ParseError::Multiple(errors) = e {
+            let errors = errors.into_iter().map(|e| ArgumentParser::locate_error(e, origins));
+            return ParseError::Multiple(errors.collect());
+        }
+        match e.position().and_then(|i| origins.get(i).cloned().flatten()) {
+            Some((path, line)) => ParseError::InResponseFile { path, line, error: Box::new(e) },
+            None => e,
+        }
+    }
+
+    fn parse_expanded(&self, raw_arguments: &[OsString], keep_unknown: bool) -> Result<ParsedArgs, ParseError> {
+        let mut cl_arguments: Vec<String> = vec![];
+        for (i, arg) in raw_arguments.iter().enumerate() {
+            match arg.to_str() {
+                Some(a) => cl_arguments.push(a.to_owned()),
+                /* The program name is only used for display, and what follows
+                   `--` is also available untouched with get_trailing_os */
+                None if i == 0 || cl_arguments.iter().skip(1).any(|a| a == "--") => {
+                    cl_arguments.push(arg.to_string_lossy().into_owned())
+                }
+                None => {
+                    return Err(ParseError::InvalidUtf8 {
+                        token: arg.to_string_lossy().into_owned(),
+                        position: i,
+                    })
+                }
+            }
         }
+        let context = ParseContext {
+            program_name: self.program_name(),
+            collect_errors: self.collect_errors,
+            reporter: self.reporter.as_ref(),
+            verbose: self.verbose,
+            raw_arguments,
+            keep_unknown,
+        };
+        self.parse_cl_arguments(&cl_arguments, &context)
+    }
+
+    /* Parses argv (argv[0] being the program) without modifying the parser.
+       Accepts any iterator of String, &str, OsString, ... */
+    pub fn try_parse_from<I, T>(&self, arguments: I) -> Result<ParsedArgs, ParseError>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<OsString>,
+    {
+        let raw_arguments: Vec<OsString> = arguments.into_iter().map(Into::into).collect();
+        self.parse_os(&raw_arguments, false)
+    }
+
+    /* Like try_parse_from, but the tokens no argument takes (unknown options,
+       extra values) are returned in order instead of being errors, e.g. to
+       forward them to a child process. The values of an unknown option are
+       only kept together with it when attached (--opt=value). */
+    pub fn parse_known_args<I, T>(&self, arguments: I) -> Result<(ParsedArgs, Vec<String>), ParseError>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<OsString>,
+    {
+        let raw_arguments: Vec<OsString> = arguments.into_iter().map(Into::into).collect();
+        let mut parsed = self.parse_os(&raw_arguments, true)?;
+        let unknown = parsed.take_unknown();
+        Ok((parsed, unknown))
+    }
+
+    pub fn parse(&self) -> Result<ParsedArgs, ParseError> {
+        self.try_parse_from(env::args_os())
     }
 
-    pub fn parse_arguments_from_text(&mut self, text: String) {
-        let mut arguments: Vec<String> = text.split(" ").map(|f| f.to_owned()).collect();
+    /* The text is split like a shell would, quotes and escapes included */
+    pub fn parse_from_text(&self, text: &str) -> Result<ParsedArgs, ParseError> {
+        let mut arguments = split_command_line(text)?;
         arguments.insert(0, "program_name".to_owned());
-        let mut used_arguments: Vec<bool> = vec![false; arguments.len()];
-        for arg_ix in 0..self.arguments.len() { 
-            self.parse_arg(&arguments, &mut used_arguments, arg_ix);
+        self.try_parse_from(arguments)
+    }
+
+    /* Parse and keep the values in the parser, to be read with get_value */
+    pub fn parse_arguments(&mut self) -> Result<(), ParseError> {
+        self.last_parsed = None;
+        self.last_parsed = Some(self.parse()?);
+        Ok(())
+    }
+
+    pub fn parse_arguments_from_text(&mut self, text: String) -> Result<(), ParseError> {
+        self.last_parsed = None;
+        self.last_parsed = Some(self.parse_from_text(&text)?);
+        Ok(())
+    }
+
+    /* Adds an optional (e.g. Arg::new("--config")) naming a configuration
+       file, which then has to exist */
+    pub fn add_config_argument(&mut self, arg: Arg) -> Result<(), String> {
+        let arg = arg.string();
+        if !arg.name.starts_with('-') {
+            return Err("The configuration file arg must be an optional!".to_owned());
         }
+        let name = Argument::parse_name(&arg.name).unwrap_or_default();
+        self.arg(arg)?;
+        self.config_argument = Some(name);
+        Ok(())
     }
 
-    pub fn get_value<T: ExtractFromContents>(&self, arg: &str) -> Option<T> {
-        let mut ret: Option<Content> = None;
-        for a in self.arguments.iter() {
-            if &a.name == arg {
-                ret = a.get_data();
+    /* At most one of these optionals or flags can be given (exactly one when
+       `necessary`), e.g. &["json", "yaml", "table"] */
+    pub fn add_mutually_exclusive_group(&mut self, names: &[&str], necessary: bool) -> Result<(), String> {
+        if names.len() < 2 {
+            return Err("A mutually exclusive group needs two args at least!".to_owned());
+        }
+        for name in names {
+            match self.arguments.iter().find(|a| a.name == *name) {
+                None => return Err(format!("Arg '{name}' does not exist!")),
+                Some(a) if a.get_type() == ArgumentType::Positional => {
+                    return Err(format!("Positional arg '{name}' cannot be mutually exclusive!"))
+                }
+                Some(_) => {}
+            }
+            if self.exclusive_groups.iter().any(|(g, _)| g.iter().any(|n| n == name)) {
+                return Err(format!("Arg '{name}' is already in a mutually exclusive group!"));
             }
         }
+        self.exclusive_groups
+            .push((names.iter().map(|n| n.to_string()).collect(), necessary));
+        Ok(())
+    }
 
-        match ret {
-            Some(c) => c.get_value(),
-            None => None,
+    /* Registers a subcommand: the first token matching `name` hands the rest
+       of the command line over to `parser`, with its own help and checks. */
+    pub fn add_subcommand(&mut self, name: &str, parser: ArgumentParser) -> Result<(), String> {
+        if name.is_empty() || name.starts_with('-') {
+            return Err("Invalid name for subcommand!".to_owned());
+        }
+        if self.subcommands.iter().any(|(n, _)| n == name) {
+            return Err(format!("Subcommand '{name}' already exists!"));
+        }
+        self.subcommands.push((name.to_owned(), parser));
+        Ok(())
+    }
+
+    /* Name of the subcommand given on the command line, if any */
+    pub fn get_subcommand(&self) -> Option<&str> {
+        self.last_parsed.as_ref().and_then(|p| p.get_subcommand())
+    }
+
+    pub fn get_subcommand_args(&self) -> Option<&ParsedArgs> {
+        self.last_parsed.as_ref().and_then(|p| p.get_subcommand_args())
+    }
+
+    pub fn get_subcommand_parser(&self, name: &str) -> Option<&ArgumentParser> {
+        self.subcommands
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, p)| p)
+    }
+
+    /* Value of the last parse_arguments call, or the default value */
+    pub fn get_value<T: ExtractFromContents>(&self, arg: &str) -> Option<T> {
+        self.get_content(arg).and_then(|c| c.get_value())
+    }
+
+    fn get_content(&self, arg: &str) -> Option<Content> {
+        match &self.last_parsed {
+            Some(p) => p.get_content(arg).cloned(),
+            None => self
+                .arguments
+                .iter()
+                .find(|a| a.name == arg)
+                .and_then(|a| a.get_default()),
         }
     }
 
     /* ++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++ */
     /* API Aux. +++++++++++++++++++++++++++++++++++++++++++++++++++++++++ */
     pub fn print_data(&self) {
-        println!("##### Arguments");
+        let mut text = String::from("##### Arguments\n");
         for d in self.arguments.iter() {
-            let data = if let Some(c) = d.get_data() {
+            let data = if let Some(c) = self.get_content(&d.name) {
                 c.get_value_str()
             } else {
                 "None".to_string()
             };
-            println!("{:?} [{:?}] : {:?}", d.name, d.data_type, data);
+            text.push_str(&format!("{:?} [{:?}] : {:?}\n", d.name, d.data_type, data));
         }
-        println!("------");
+        text.push_str("------");
+        self.reporter.write(OutputKind::Data, &text);
+    }
+
+    /* Prints a parse error (all of them when collected) to the reporter */
+    pub fn print_error(&self, error: &ParseError) {
+        self.reporter.write(OutputKind::Error, &error.report());
+    }
+}
+
+impl Default for ArgumentParser {
+    fn default() -> Self {
+        ArgumentParser::new()
     }
 }
@@ -0,0 +1,33 @@
+//! Destination of everything the library prints.
+
+/// What a message printed by the parser is.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutputKind {
+    Help,
+    Error,
+    Warning,
+    /// Values shown by `print_data`.
+    Data,
+    /// Parsing steps, only produced in verbose mode.
+    Trace,
+}
+
+/// Receives all the output of an `ArgumentParser` (see `set_reporter`).
+///
+/// `text` is one complete message without the final newline.
+pub trait Reporter: Send + Sync {
+    fn write(&self, kind: OutputKind, text: &str);
+}
+
+/// Default reporter: help and data go to stdout, the rest to stderr.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StdReporter;
+
+impl Reporter for StdReporter {
+    fn write(&self, kind: OutputKind, text: &str) {
+        match kind {
+            OutputKind::Help | OutputKind::Data => println!("{text}"),
+            OutputKind::Error | OutputKind::Warning | OutputKind::Trace => eprintln!("{text}"),
+        }
+    }
+}
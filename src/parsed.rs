@@ -0,0 +1,129 @@
+use std::ffi::OsString;
+
+use crate::argument::{Content, ExtractFromContents};
+
+/// Where the value of an argument comes from.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValueSource {
+    Default,
+    Environment,
+    ConfigFile { path: String },
+    /// `index` is the argv position of the (winning) occurrence.
+    CommandLine { index: usize },
+}
+
+/// Values of one parsed command line, returned by `ArgumentParser::try_parse_from`.
+///
+/// Holds the value (given or default) of every argument by name and, when a
+/// subcommand was given, its name and values. The parser itself is left
+/// untouched, so one parser can be shared and used for many command lines.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParsedArgs {
+    values: Vec<(String, Content, ValueSource)>,
+    /* Times each argument is given on the command line */
+    occurrences: Vec<(String, usize)>,
+    subcommand: Option<(String, Box<ParsedArgs>)>,
+    trailing: Vec<OsString>,
+    /* Tokens no argument took, with parse_known_args */
+    unknown: Vec<String>,
+}
+
+impl ParsedArgs {
+    pub(crate) fn new() -> Self {
+        ParsedArgs::default()
+    }
+
+    pub(crate) fn set_value(&mut self, name: &str, value: Content, source: ValueSource) {
+        match self.values.iter_mut().find(|(n, _, _)| n == name) {
+            Some((_, v, s)) => {
+                *v = value;
+                *s = source;
+            }
+            None => self.values.push((name.to_owned(), value, source)),
+        }
+    }
+
+    pub(crate) fn set_occurrences(&mut self, name: &str, count: usize) {
+        self.occurrences.push((name.to_owned(), count));
+    }
+
+    /* Moves the command line indices by `offset`, for the values of a
+       subcommand parser that only saw the tail of the command line */
+    pub(crate) fn shifted(mut self, offset: usize) -> ParsedArgs {
+        for (_, _, source) in &mut self.values {
+            if let ValueSource::CommandLine { index } = source {
+                *index += offset;
+            }
+        }
+        if let Some((name, sub)) = self.subcommand {
+            self.subcommand = Some((name, Box::new(sub.shifted(offset))));
+        }
+        self
+    }
+
+    pub(crate) fn set_subcommand(&mut self, name: &str, args: ParsedArgs) {
+        self.subcommand = Some((name.to_owned(), Box::new(args)));
+    }
+
+    pub(crate) fn set_trailing(&mut self, trailing: Vec<OsString>) {
+        self.trailing = trailing;
+    }
+
+    pub(crate) fn set_unknown(&mut self, unknown: Vec<String>) {
+        self.unknown = unknown;
+    }
+
+    /* Unknown tokens of this command line and of its subcommand */
+    pub(crate) fn take_unknown(&mut self) -> Vec<String> {
+        let mut unknown = std::mem::take(&mut self.unknown);
+        if let Some((_, sub)) = &mut self.subcommand {
+            unknown.extend(sub.take_unknown());
+        }
+        unknown
+    }
+
+    pub fn get_content(&self, name: &str) -> Option<&Content> {
+        self.values.iter().find(|(n, _, _)| n == name).map(|(_, v, _)| v)
+    }
+
+    /* None when the argument has no value */
+    pub fn value_source(&self, name: &str) -> Option<&ValueSource> {
+        self.values.iter().find(|(n, _, _)| n == name).map(|(_, _, s)| s)
+    }
+
+    /* Whether the argument is given on the command line */
+    pub fn is_present(&self, name: &str) -> bool {
+        self.occurrences(name) > 0
+    }
+
+    /* Times the argument is given on the command line (-vvv is 3) */
+    pub fn occurrences(&self, name: &str) -> usize {
+        self.occurrences
+            .iter()
+            .find(|(n, _)| n == name)
+            .map_or(0, |(_, c)| *c)
+    }
+
+    pub fn get_value<T: ExtractFromContents>(&self, name: &str) -> Option<T> {
+        self.get_content(name).and_then(|c| c.get_value())
+    }
+
+    /* Name of the subcommand given on the command line, if any */
+    pub fn get_subcommand(&self) -> Option<&str> {
+        self.subcommand.as_ref().map(|(n, _)| n.as_str())
+    }
+
+    pub fn get_subcommand_args(&self) -> Option<&ParsedArgs> {
+        self.subcommand.as_ref().map(|(_, a)| a.as_ref())
+    }
+
+    /* Arguments given after `--`, not parsed as options */
+    pub fn get_trailing(&self) -> Vec<String> {
+        self.trailing.iter().map(|a| a.to_string_lossy().into_owned()).collect()
+    }
+
+    /* Same as given, even when they are not valid UTF-8 */
+    pub fn get_trailing_os(&self) -> &[OsString] {
+        &self.trailing
+    }
+}
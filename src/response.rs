@@ -0,0 +1,150 @@
+//! Response files: `@path` arguments replaced by the arguments listed in
+//! the file (see `ArgumentParser::set_response_files`).
+//!
+//! Each line is split like a shell would, so a file can hold one argument
+//! per line or whole command lines. Blank lines and lines starting with `#`
+//! are skipped, and files can include others with `@path`.
+use std::ffi::OsString;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::{split_command_line, ParseError};
+
+/* Where a token comes from: a file and a line, or argv */
+pub(crate) type Origin = Option<(String, usize)>;
+
+/* Expands every @path of argv (argv[0] excepted), until `--`. Each token
+   comes with its origin, for the errors. */
+pub(crate) fn expand_response_files(arguments: &[OsString]) -> Result<Vec<(OsString, Origin)>, ParseError> {
+    let mut expanded: Vec<(OsString, Origin)> = arguments.iter().take(1).map(|t| (t.clone(), None)).collect();
+    let mut ended = false;
+    for token in arguments.iter().skip(1) {
+        expand(token.clone(), None, &mut vec![], &mut expanded, &mut ended)?;
+    }
+    Ok(expanded)
+}
+
+fn expand(
+    token: OsString,
+    origin: Origin,
+    /* Files being expanded, to stop cycles */
+    stack: &mut Vec<PathBuf>,
+    expanded: &mut Vec<(OsString, Origin)>,
+    ended: &mut bool,
+) -> Result<(), ParseError> {
+    let path = match token.to_str().and_then(|t| t.strip_prefix('@')) {
+        Some(p) if !*ended && !p.is_empty() => p.to_owned(),
+        _ => {
+            *ended |= token == "--";
+            expanded.push((token, origin));
+            return Ok(());
+        }
+    };
+
+    /* Problems with the file itself point at the line including it */
+    let error = |message: String| match &origin {
+        Some((file, line)) => ParseError::ResponseFile {
+            path: file.clone(),
+            line: Some(*line),
+            message,
+        },
+        None => ParseError::ResponseFile {
+            path: path.clone(),
+            line: None,
+            message,
+        },
+    };
+    let canonical = fs::canonicalize(&path).map_err(|e| error(format!("cannot read @{path}: {e}")))?;
+    if stack.contains(&canonical) {
+        return Err(error(format!("@{path} includes itself")));
+    }
+    let text = fs::read_to_string(&path).map_err(|e| error(format!("cannot read @{path}: {e}")))?;
+
+    stack.push(canonical);
+    for (n, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let tokens = split_command_line(line).map_err(|e| ParseError::ResponseFile {
+            path: path.clone(),
+            line: Some(n + 1),
+            message: e.to_string(),
+        })?;
+        for t in tokens {
+            expand(OsString::from(t), Some((path.clone(), n + 1)), stack, expanded, ended)?;
+        }
+    }
+    stack.pop();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /* Response files only this test uses, in a directory of their own */
+    fn directory(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("migparser-{}-{name}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        for (file, text) in files {
+            fs::write(dir.join(file), text).unwrap();
+        }
+        dir
+    }
+
+    fn at(dir: &std::path::Path, file: &str) -> String {
+        format!("@{}", dir.join(file).display())
+    }
+
+    fn tokens(expanded: Vec<(OsString, Origin)>) -> Vec<String> {
+        expanded.into_iter().map(|(t, _)| t.into_string().unwrap()).collect()
+    }
+
+    #[test]
+    fn comments_and_blank_lines_are_skipped() {
+        let dir = directory("comments", &[("args.txt", "# options\n--level 3\n\n  --name 'a b'\n")]);
+        let argv = ["p".into(), at(&dir, "args.txt").into(), "x".into()];
+        let expanded = expand_response_files(&argv).unwrap();
+        let path = dir.join("args.txt").display().to_string();
+        assert_eq!(expanded[1].1, Some((path, 2)));
+        assert_eq!(tokens(expanded), ["p", "--level", "3", "--name", "a b", "x"]);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn nested_files_and_terminator() {
+        let dir = directory("nested", &[("inner.txt", "-c -d\n")]);
+        let inner = at(&dir, "inner.txt");
+        fs::write(dir.join("outer.txt"), format!("-a\n{inner}\n-b\n")).unwrap();
+        let argv = ["p".into(), at(&dir, "outer.txt").into(), "--".into(), inner.clone().into()];
+        let expanded = expand_response_files(&argv).unwrap();
+        assert_eq!(tokens(expanded), ["p", "-a", "-c", "-d", "-b", "--", inner.as_str()]);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn cycles_are_errors() {
+        let dir = directory("cycle", &[("a.txt", ""), ("b.txt", "")]);
+        fs::write(dir.join("a.txt"), at(&dir, "b.txt")).unwrap();
+        fs::write(dir.join("b.txt"), format!("-x\n{}\n", at(&dir, "a.txt"))).unwrap();
+        let argv = ["p".into(), at(&dir, "a.txt").into()];
+        match expand_response_files(&argv) {
+            Err(ParseError::ResponseFile { path, line, .. }) => {
+                assert_eq!(path, dir.join("b.txt").display().to_string());
+                assert_eq!(line, Some(2));
+            }
+            other => panic!("expected a ResponseFile error, got {other:?}"),
+        }
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn unterminated_quote_points_at_the_line() {
+        let dir = directory("quote", &[("args.txt", "-a\n-b 'oops\n")]);
+        let argv = ["p".into(), at(&dir, "args.txt").into()];
+        let error = expand_response_files(&argv).unwrap_err();
+        assert!(matches!(error, ParseError::ResponseFile { line: Some(2), .. }));
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
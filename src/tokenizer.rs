@@ -0,0 +1,126 @@
+//! POSIX-shell-like splitting of a command line given as text.
+use crate::ParseError;
+
+enum Quote {
+    None,
+    Single,
+    Double,
+}
+
+/// Splits `text` into arguments the way a POSIX shell would (without any
+/// expansion): whitespace runs separate arguments, single quotes keep
+/// everything literally, double quotes allow `\"`, `\\`, `\$` and `` \` ``
+/// escapes, and a backslash outside quotes escapes the next character.
+pub fn split_command_line(text: &str) -> Result<Vec<String>, ParseError> {
+    let mut tokens: Vec<String> = vec![];
+    let mut current = String::new();
+    /* Whether a token has started, so that "" gives an empty argument */
+    let mut in_token = false;
+    let mut quote = Quote::None;
+    let mut quote_start = 0;
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        match quote {
+            Quote::None => match c {
+                c if c.is_whitespace() => {
+                    if in_token {
+                        tokens.push(std::mem::take(&mut current));
+                        in_token = false;
+                    }
+                }
+                '\'' => {
+                    quote = Quote::Single;
+                    quote_start = i;
+                    in_token = true;
+                }
+                '"' => {
+                    quote = Quote::Double;
+                    quote_start = i;
+                    in_token = true;
+                }
+                '\\' => {
+                    /* A trailing backslash is kept as is */
+                    current.push(chars.next().map(|(_, n)| n).unwrap_or('\\'));
+                    in_token = true;
+                }
+                _ => {
+                    current.push(c);
+                    in_token = true;
+                }
+            },
+            Quote::Single => match c {
+                '\'' => quote = Quote::None,
+                _ => current.push(c),
+            },
+            Quote::Double => match c {
+                '"' => quote = Quote::None,
+                '\\' => match chars.peek() {
+                    Some((_, n)) if matches!(n, '"' | '\\' | '$' | '`') => {
+                        current.push(*n);
+                        chars.next();
+                    }
+                    _ => current.push(c),
+                },
+                _ => current.push(c),
+            },
+        }
+    }
+
+    match quote {
+        Quote::None => {}
+        Quote::Single => {
+            return Err(ParseError::UnterminatedQuote { quote: '\'', offset: quote_start })
+        }
+        Quote::Double => {
+            return Err(ParseError::UnterminatedQuote { quote: '"', offset: quote_start })
+        }
+    }
+    if in_token {
+        tokens.push(current);
+    }
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn split(text: &str) -> Vec<String> {
+        split_command_line(text).unwrap()
+    }
+
+    #[test]
+    fn whitespace_separates_arguments() {
+        assert_eq!(split("  prog -a\t1 \n --name x "), ["prog", "-a", "1", "--name", "x"]);
+        assert!(split("   ").is_empty());
+    }
+
+    #[test]
+    fn quotes() {
+        assert_eq!(split(r#"prog 'a b' "c d" e'f g'h"#), ["prog", "a b", "c d", "ef gh"]);
+        assert_eq!(split(r#"prog '' """#), ["prog", "", ""]);
+        assert_eq!(split(r#"prog 'say "hi"' "it's""#), ["prog", r#"say "hi""#, "it's"]);
+    }
+
+    #[test]
+    fn escapes() {
+        assert_eq!(split(r"prog a\ b \'c\'"), ["prog", "a b", "'c'"]);
+        assert_eq!(split(r#"prog "a \"b\" \\ \$HOME \n""#), ["prog", r#"a "b" \ $HOME \n"#]);
+        /* Nothing is escaped between single quotes */
+        assert_eq!(split(r"prog 'a\ b'"), ["prog", r"a\ b"]);
+        assert_eq!(split(r"prog a\"), ["prog", r"a\"]);
+    }
+
+    #[test]
+    fn unterminated_quote() {
+        assert_eq!(
+            split_command_line("prog 'abc"),
+            Err(ParseError::UnterminatedQuote { quote: '\'', offset: 5 })
+        );
+        assert_eq!(
+            split_command_line(r#"prog "a\" b"#),
+            Err(ParseError::UnterminatedQuote { quote: '"', offset: 5 })
+        );
+    }
+}
@@ -0,0 +1,96 @@
+use std::fs;
+use std::path::PathBuf;
+
+use migparser::{Arg, ArgumentParser, DataType, NArgs, ParseError, ValueSource};
+
+/* A configuration file only this test uses */
+fn config_file(name: &str, text: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("migparser-{}-{name}", std::process::id()));
+    fs::write(&path, text).unwrap();
+    path
+}
+
+#[test]
+fn command_line_then_environment_then_file_then_default() {
+    let path = config_file("precedence.ini", "cli = 1\nenv = 1\nfile = 1\n");
+    let mut parser = ArgumentParser::new();
+    parser.set_config_file(path.to_str().unwrap());
+    parser.arg(Arg::new("--cli").int().env("MP_CONFIG_TEST_CLI")).unwrap();
+    parser.arg(Arg::new("--env").int().env("MP_CONFIG_TEST_ENV")).unwrap();
+    parser.arg(Arg::new("--file").int()).unwrap();
+    parser.arg(Arg::new("--default").int().default(4)).unwrap();
+    std::env::set_var("MP_CONFIG_TEST_CLI", "2");
+    std::env::set_var("MP_CONFIG_TEST_ENV", "2");
+
+    let parsed = parser.try_parse_from(["p", "--cli", "3"]).unwrap();
+    assert_eq!(parsed.get_value::<i32>("cli"), Some(3));
+    assert_eq!(parsed.value_source("cli"), Some(&ValueSource::CommandLine { index: 1 }));
+    assert_eq!(parsed.get_value::<i32>("env"), Some(2));
+    assert_eq!(parsed.value_source("env"), Some(&ValueSource::Environment));
+    assert_eq!(parsed.get_value::<i32>("file"), Some(1));
+    assert_eq!(
+        parsed.value_source("file"),
+        Some(&ValueSource::ConfigFile { path: path.to_str().unwrap().to_owned() })
+    );
+    assert_eq!(parsed.get_value::<i32>("default"), Some(4));
+    assert_eq!(parsed.value_source("default"), Some(&ValueSource::Default));
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn value_of_the_wrong_type() {
+    let path = config_file("type.ini", "count = many\n");
+    let mut parser = ArgumentParser::new();
+    parser.set_config_file(path.to_str().unwrap());
+    parser.arg(Arg::new("--count").int()).unwrap();
+
+    let error = parser.try_parse_from(["p"]).unwrap_err();
+    assert_eq!(
+        error,
+        ParseError::InvalidConfigValue {
+            path: path.to_str().unwrap().to_owned(),
+            key: "count".to_owned(),
+            raw: "many".to_owned(),
+            expected: DataType::Int,
+        }
+    );
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn config_argument_names_the_file() {
+    let path = config_file("argument.ini", "level = 5\n");
+    let mut parser = ArgumentParser::new();
+    parser.add_config_argument(Arg::new("--config")).unwrap();
+    parser.arg(Arg::new("--level").int()).unwrap();
+
+    let parsed = parser.try_parse_from(["p", "--config", path.to_str().unwrap()]).unwrap();
+    assert_eq!(parsed.get_value::<i32>("level"), Some(5));
+    assert_eq!(parsed.get_value::<String>("config").as_deref(), path.to_str());
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn config_argument_errors_are_reported_once() {
+    let mut parser = ArgumentParser::new();
+    parser.set_collect_errors(true);
+    parser.add_config_argument(Arg::new("--config").required()).unwrap();
+
+    let error = parser.try_parse_from(["p"]).unwrap_err();
+    assert_eq!(error, ParseError::MissingRequired { arg: "config".to_owned() });
+}
+
+#[test]
+fn optional_value_left_out_on_the_command_line() {
+    let mut parser = ArgumentParser::new();
+    parser.arg(Arg::new("--lvl").int().num_args(NArgs::Optional).env("MP_CONFIG_TEST_LVL")).unwrap();
+    parser.arg(Arg::new("--out").int().num_args(NArgs::Optional).default(3).env("MP_CONFIG_TEST_OUT")).unwrap();
+    std::env::set_var("MP_CONFIG_TEST_LVL", "7");
+    std::env::set_var("MP_CONFIG_TEST_OUT", "7");
+
+    let parsed = parser.try_parse_from(["p", "--lvl", "--out"]).unwrap();
+    assert!(parsed.is_present("lvl"));
+    assert_eq!(parsed.get_value::<i32>("lvl"), None);
+    assert_eq!(parsed.get_value::<i32>("out"), Some(3));
+    assert_eq!(parsed.value_source("out"), Some(&ValueSource::CommandLine { index: 2 }));
+}
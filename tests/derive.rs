@@ -0,0 +1,97 @@
+#![cfg(feature = "derive")]
+
+use migparser::{MigParser, ParseError};
+
+/// Build things.
+#[derive(MigParser, Debug, PartialEq)]
+struct Cli {
+    /// Input file
+    #[migparser(positional)]
+    input: String,
+    /// Number of jobs
+    #[migparser(short = 'j', default = 2)]
+    max_jobs: u32,
+    #[migparser(short = 'v')]
+    verbose: bool,
+    #[migparser(count, short = 'q')]
+    quiet: u32,
+    level: Option<i32>,
+    #[migparser(append, short = 'I')]
+    include: Vec<String>,
+    #[migparser(nargs = "+")]
+    ratio: Vec<f32>,
+}
+
+#[test]
+fn fields_from_the_command_line() {
+    let cli = Cli::try_from_text("in.txt -j 4 -vqq --level -3 -I a -I b --ratio 0.5 1").unwrap();
+    assert_eq!(
+        cli,
+        Cli {
+            input: "in.txt".to_owned(),
+            max_jobs: 4,
+            verbose: true,
+            quiet: 2,
+            level: Some(-3),
+            include: vec!["a".to_owned(), "b".to_owned()],
+            ratio: vec![0.5, 1.0],
+        }
+    );
+}
+
+#[test]
+fn defaults_and_absent_fields() {
+    let cli = Cli::try_from_text("in.txt").unwrap();
+    assert_eq!(cli.max_jobs, 2);
+    assert!(!cli.verbose);
+    assert_eq!(cli.quiet, 0);
+    assert_eq!(cli.level, None);
+    assert!(cli.include.is_empty());
+}
+
+#[test]
+fn necessary_fields() {
+    let error = Cli::try_from_text("-j 4").unwrap_err();
+    assert_eq!(error, ParseError::MissingRequired { arg: "input".to_owned() });
+}
+
+#[test]
+fn parser_can_be_used_directly() {
+    let parsed = Cli::parser().try_parse_from(["build", "in.txt", "--max-jobs", "8"]).unwrap();
+    assert_eq!(parsed.get_value::<u32>("max-jobs"), Some(8));
+    assert_eq!(Cli::from_parsed(&parsed).unwrap().max_jobs, 8);
+}
+
+#[derive(MigParser, Debug, PartialEq)]
+struct Paths {
+    #[migparser(positional)]
+    input: Option<String>,
+    #[migparser(positional, default = "out.txt")]
+    output: String,
+    #[migparser(positional, default = 2)]
+    n: u32,
+}
+
+#[test]
+fn positionals_that_can_be_left_out() {
+    let paths = Paths::try_from_text("").unwrap();
+    assert_eq!(paths, Paths { input: None, output: "out.txt".to_owned(), n: 2 });
+
+    let paths = Paths::try_from_text("a b 3").unwrap();
+    assert_eq!(paths, Paths { input: Some("a".to_owned()), output: "b".to_owned(), n: 3 });
+}
+
+#[derive(MigParser, Debug, PartialEq)]
+struct Defaults {
+    #[migparser(default = "out.txt")]
+    output: String,
+    #[migparser(default = String::from("log"))]
+    log: String,
+}
+
+#[test]
+fn string_defaults() {
+    let defaults = Defaults::try_from_text("--log x").unwrap();
+    assert_eq!(defaults, Defaults { output: "out.txt".to_owned(), log: "x".to_owned() });
+    assert_eq!(Defaults::try_from_text("").unwrap().log, "log");
+}
@@ -0,0 +1,75 @@
+use std::sync::{Arc, Mutex};
+
+use migparser::{Arg, ArgumentParser, OutputKind, ParseError, Reporter};
+
+/* Keeps what the parser prints */
+#[derive(Clone, Default)]
+struct Capture(Arc<Mutex<Vec<(OutputKind, String)>>>);
+
+impl Reporter for Capture {
+    fn write(&self, kind: OutputKind, text: &str) {
+        self.0.lock().unwrap().push((kind, text.to_owned()));
+    }
+}
+
+impl Capture {
+    fn kinds(&self) -> Vec<OutputKind> {
+        self.0.lock().unwrap().iter().map(|(k, _)| *k).collect()
+    }
+}
+
+fn parser(capture: &Capture) -> ArgumentParser {
+    let mut parser = ArgumentParser::new();
+    parser.set_program_name("p");
+    parser.set_reporter(capture.clone());
+    parser.arg(Arg::new("--level").int()).unwrap();
+    parser
+}
+
+#[test]
+fn parsing_prints_nothing_by_default() {
+    let capture = Capture::default();
+    let parser = parser(&capture);
+    parser.try_parse_from(["p", "--level", "3"]).unwrap();
+    parser.try_parse_from(["p", "--level", "x"]).unwrap_err();
+    assert!(capture.kinds().is_empty());
+}
+
+#[test]
+fn help_and_errors_go_through_the_reporter() {
+    let capture = Capture::default();
+    let mut parser = parser(&capture);
+    assert_eq!(parser.try_parse_from(["p", "--help"]), Err(ParseError::HelpRequested));
+    parser.print_error(&ParseError::MissingRequired { arg: "level".to_owned() });
+    parser.parse_arguments_from_text("--level 3".to_owned()).unwrap();
+    parser.print_data();
+
+    assert_eq!(capture.kinds(), [OutputKind::Help, OutputKind::Error, OutputKind::Data]);
+    let output = capture.0.lock().unwrap();
+    assert!(output[0].1.starts_with("Usage: p [-h] [--level LEVEL]"));
+    assert!(output[1].1.contains("Necessary argument 'level' is not present"));
+}
+
+#[test]
+fn ignored_arguments_give_a_warning() {
+    let capture = Capture::default();
+    let mut parser = parser(&capture);
+    parser.set_strict(false);
+    parser.try_parse_from(["p", "extra", "--level", "3"]).unwrap();
+
+    assert_eq!(capture.kinds(), [OutputKind::Warning]);
+    assert!(capture.0.lock().unwrap()[0].1.contains("ignoring argument 'extra' at position 1"));
+}
+
+#[test]
+fn verbose_mode_traces_through_the_reporter() {
+    let capture = Capture::default();
+    let mut parser = parser(&capture);
+    parser.set_verbose(true);
+    parser.try_parse_from(["p", "--level", "3"]).unwrap();
+
+    let kinds = capture.kinds();
+    assert!(!kinds.is_empty());
+    assert!(kinds.iter().all(|k| *k == OutputKind::Trace));
+    assert!(capture.0.lock().unwrap().iter().any(|(_, text)| text.contains("level = 3")));
+}
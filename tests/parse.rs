@@ -0,0 +1,356 @@
+use migparser::{Arg, ArgumentParser, DuplicatePolicy, ListType, NArgs, OutputKind, ParseError, Reporter};
+
+/* Keeps the help out of the test output */
+struct Silent;
+
+impl Reporter for Silent {
+    fn write(&self, _kind: OutputKind, _text: &str) {}
+}
+
+fn parser() -> ArgumentParser {
+    let mut parser = ArgumentParser::new();
+    parser.set_collect_errors(true);
+    parser
+}
+
+#[test]
+fn collect_mode_resumes_after_an_unexpected_value() {
+    let mut parser = parser();
+    parser.arg(Arg::new("--flag").flag()).unwrap();
+    parser.arg(Arg::new("--req").int().required()).unwrap();
+
+    let error = parser.try_parse_from(["p", "--flag=1", "--req", "5"]).unwrap_err();
+    assert_eq!(
+        error,
+        ParseError::UnexpectedValue {
+            arg: "flag".to_owned(),
+            token: "--flag=1".to_owned(),
+            position: 1,
+        }
+    );
+}
+
+#[test]
+fn extra_tokens_after_the_terminator_are_unknown() {
+    let mut parser = ArgumentParser::new();
+    parser.arg(Arg::new("file").string()).unwrap();
+
+    let error = parser.try_parse_from(["p", "--", "a", "b", "c"]).unwrap_err();
+    assert_eq!(error, ParseError::UnknownArgument { token: "b".to_owned(), position: 3 });
+
+    let (parsed, unknown) = parser.parse_known_args(["p", "--", "a", "b", "c"]).unwrap();
+    assert_eq!(parsed.get_value::<String>("file").as_deref(), Some("a"));
+    assert_eq!(unknown, ["b", "c"]);
+
+    /* Nothing is option-like after `--` */
+    parser.set_strict(false);
+    let parsed = parser.try_parse_from(["p", "--", "a", "-b"]).unwrap();
+    assert_eq!(parsed.get_trailing(), ["a", "-b"]);
+
+    /* The Trailing positional takes them all */
+    let mut parser = ArgumentParser::new();
+    parser.arg(Arg::new("rest").list(ListType::String).trailing()).unwrap();
+    let (_, unknown) = parser.parse_known_args(["p", "--", "a", "b"]).unwrap();
+    assert!(unknown.is_empty());
+}
+
+#[test]
+fn fixed_values_stop_at_identifiers() {
+    let mut parser = ArgumentParser::new();
+    parser.arg(Arg::new("--opt").string()).unwrap();
+    parser.arg(Arg::new("--flag").flag()).unwrap();
+    parser.arg(Arg::new("--num").int()).unwrap();
+
+    let error = parser.try_parse_from(["p", "--opt", "--flag"]).unwrap_err();
+    assert_eq!(
+        error,
+        ParseError::NotEnoughValues {
+            arg: "opt".to_owned(),
+            token: "--opt".to_owned(),
+            position: 1,
+            expected: 1,
+            found: 0,
+        }
+    );
+    let error = parser.try_parse_from(["p", "--opt", "--", "x"]).unwrap_err();
+    assert!(matches!(error, ParseError::NotEnoughValues { found: 0, .. }));
+
+    /* Dashes are fine otherwise */
+    let parsed = parser.try_parse_from(["p", "--num", "-5", "--opt", "-x"]).unwrap();
+    assert_eq!(parsed.get_value::<i32>("num"), Some(-5));
+    assert_eq!(parsed.get_value::<String>("opt").as_deref(), Some("-x"));
+}
+
+#[test]
+fn collect_mode_resumes_after_missing_values() {
+    let mut parser = parser();
+    parser.arg(Arg::new("--pair").list(ListType::Int).nargs(2)).unwrap();
+    parser.arg(Arg::new("--req").int().required()).unwrap();
+
+    let error = parser.try_parse_from(["p", "--pair", "1", "--req", "5"]).unwrap_err();
+    assert_eq!(
+        error,
+        ParseError::NotEnoughValues {
+            arg: "pair".to_owned(),
+            token: "--pair".to_owned(),
+            position: 1,
+            expected: 2,
+            found: 1,
+        }
+    );
+}
+
+#[test]
+fn bundled_flags_and_attached_values() {
+    let mut parser = ArgumentParser::new();
+    parser.arg(Arg::new("--verbose").alias("-v").count()).unwrap();
+    parser.arg(Arg::new("--all").alias("-a").flag()).unwrap();
+    parser.arg(Arg::new("--num").alias("-n").int()).unwrap();
+    parser.arg(Arg::new("--name").string()).unwrap();
+
+    let parsed = parser.try_parse_from(["p", "-vvn5", "--name=a=b"]).unwrap();
+    assert_eq!(parsed.get_value::<u32>("verbose"), Some(2));
+    assert_eq!(parsed.get_value::<bool>("all"), Some(false));
+    assert_eq!(parsed.get_value::<i32>("num"), Some(5));
+    assert_eq!(parsed.get_value::<String>("name").as_deref(), Some("a=b"));
+
+    /* The value can also be the next token, or empty when attached */
+    let parsed = parser.try_parse_from(["p", "-av", "-n", "7", "--name="]).unwrap();
+    assert_eq!(parsed.get_value::<u32>("verbose"), Some(1));
+    assert_eq!(parsed.get_value::<bool>("all"), Some(true));
+    assert_eq!(parsed.get_value::<i32>("num"), Some(7));
+    assert_eq!(parsed.get_value::<String>("name").as_deref(), Some(""));
+
+    /* A bundle with an unknown letter is an unknown argument */
+    let error = parser.try_parse_from(["p", "-vx"]).unwrap_err();
+    assert_eq!(error, ParseError::UnknownArgument { token: "-vx".to_owned(), position: 1 });
+}
+
+#[test]
+fn variable_number_of_values() {
+    let mut parser = ArgumentParser::new();
+    parser.arg(Arg::new("--any").list(ListType::Int).num_args(NArgs::ZeroOrMore)).unwrap();
+    parser.arg(Arg::new("--some").list(ListType::Int).num_args(NArgs::OneOrMore)).unwrap();
+    parser.arg(Arg::new("--range").list(ListType::Int).num_args(NArgs::Range(1, 2))).unwrap();
+    parser.arg(Arg::new("--maybe").int().num_args(NArgs::Optional).default(0)).unwrap();
+
+    let parsed = parser
+        .try_parse_from(["p", "--any", "--some", "1", "-2", "--range", "3", "4", "--maybe", "5"])
+        .unwrap();
+    assert_eq!(parsed.get_value::<Vec<i32>>("any"), Some(vec![]));
+    assert_eq!(parsed.get_value::<Vec<i32>>("some"), Some(vec![1, -2]));
+    assert_eq!(parsed.get_value::<Vec<i32>>("range"), Some(vec![3, 4]));
+    assert_eq!(parsed.get_value::<i32>("maybe"), Some(5));
+
+    let error = parser.try_parse_from(["p", "--some", "--maybe"]).unwrap_err();
+    assert!(matches!(error, ParseError::NotEnoughValues { expected: 1, found: 0, .. }));
+    let error = parser.try_parse_from(["p", "--range", "1", "2", "3"]).unwrap_err();
+    assert_eq!(error, ParseError::UnknownArgument { token: "3".to_owned(), position: 4 });
+}
+
+#[test]
+fn optional_positional_keeps_its_default() {
+    let mut parser = ArgumentParser::new();
+    parser.arg(Arg::new("level").int().num_args(NArgs::Optional).default(1)).unwrap();
+
+    let parsed = parser.try_parse_from(["p"]).unwrap();
+    assert_eq!(parsed.get_value::<i32>("level"), Some(1));
+    let parsed = parser.try_parse_from(["p", "2"]).unwrap();
+    assert_eq!(parsed.get_value::<i32>("level"), Some(2));
+}
+
+#[test]
+fn help_only_where_an_identifier_is_expected() {
+    let mut parser = ArgumentParser::new();
+    parser.set_reporter(Silent);
+    parser.arg(Arg::new("--x").string()).unwrap();
+    parser.arg(Arg::new("--verbose").alias("-v").flag()).unwrap();
+
+    assert_eq!(parser.try_parse_from(["p", "-h"]), Err(ParseError::HelpRequested));
+    assert_eq!(parser.try_parse_from(["p", "-vh"]), Err(ParseError::HelpRequested));
+    assert_eq!(parser.try_parse_from(["p", "--x", "1", "--help"]), Err(ParseError::HelpRequested));
+
+    let parsed = parser.try_parse_from(["p", "--x", "-h"]).unwrap();
+    assert_eq!(parsed.get_value::<String>("x").as_deref(), Some("-h"));
+    let parsed = parser.try_parse_from(["p", "--x=--help"]).unwrap();
+    assert_eq!(parsed.get_value::<String>("x").as_deref(), Some("--help"));
+    let error = parser.try_parse_from(["p", "--", "-h"]).unwrap_err();
+    assert_eq!(error, ParseError::UnknownArgument { token: "-h".to_owned(), position: 2 });
+}
+
+fn formats(necessary: bool) -> ArgumentParser {
+    let mut parser = ArgumentParser::new();
+    parser.set_program_name("p");
+    parser.arg(Arg::new("--json").flag()).unwrap();
+    parser.arg(Arg::new("--yaml").flag()).unwrap();
+    parser.arg(Arg::new("--out").string()).unwrap();
+    parser.add_mutually_exclusive_group(&["json", "yaml"], necessary).unwrap();
+    parser
+}
+
+#[test]
+fn mutually_exclusive_arguments() {
+    let parser = formats(false);
+    assert!(parser.try_parse_from(["p", "--yaml"]).is_ok());
+    assert!(parser.try_parse_from(["p"]).is_ok());
+
+    let error = parser.try_parse_from(["p", "--yaml", "--out", "x", "--json"]).unwrap_err();
+    assert_eq!(
+        error,
+        ParseError::MutuallyExclusive {
+            first: "yaml".to_owned(),
+            second: "json".to_owned(),
+            position: 4,
+        }
+    );
+}
+
+#[test]
+fn necessary_group_ignores_defaults() {
+    /* Flags always have a default value, it does not count */
+    let parser = formats(true);
+    let error = parser.try_parse_from(["p", "--out", "x"]).unwrap_err();
+    assert_eq!(error, ParseError::MissingOneOf { args: vec!["json".to_owned(), "yaml".to_owned()] });
+    assert!(parser.try_parse_from(["p", "--json"]).is_ok());
+}
+
+#[test]
+fn mutually_exclusive_group_validation() {
+    let mut parser = formats(false);
+    parser.arg(Arg::new("--toml").flag()).unwrap();
+    parser.arg(Arg::new("file").string()).unwrap();
+
+    assert!(parser.add_mutually_exclusive_group(&["toml"], false).is_err());
+    assert!(parser.add_mutually_exclusive_group(&["toml", "xml"], false).is_err());
+    assert!(parser.add_mutually_exclusive_group(&["toml", "file"], false).is_err());
+    assert!(parser.add_mutually_exclusive_group(&["toml", "json"], false).is_err());
+    assert!(parser.add_mutually_exclusive_group(&["toml", "out"], false).is_ok());
+}
+
+#[test]
+fn mutually_exclusive_group_usage() {
+    assert!(formats(false).format_help().starts_with("Usage: p [-h] [--json | --yaml] [--out OUT]"));
+    assert!(formats(true).format_help().starts_with("Usage: p [-h] (--json | --yaml) [--out OUT]"));
+}
+
+#[test]
+fn negatable_flags() {
+    let mut parser = ArgumentParser::new();
+    parser.set_program_name("p");
+    parser.arg(Arg::new("--color").flag().negatable()).unwrap();
+
+    let parsed = parser.try_parse_from(["p", "--color", "--no-color"]).unwrap();
+    assert_eq!(parsed.get_value::<bool>("color"), Some(false));
+    let parsed = parser.try_parse_from(["p", "--no-color", "--color"]).unwrap();
+    assert_eq!(parsed.get_value::<bool>("color"), Some(true));
+    let parsed = parser.try_parse_from(["p"]).unwrap();
+    assert_eq!(parsed.get_value::<bool>("color"), Some(false));
+
+    assert!(parser.format_help().starts_with("Usage: p [-h] [--[no-]color]"));
+    assert!(parser.format_help().contains("\n  --[no-]color"));
+}
+
+#[test]
+fn negatable_needs_a_long_flag() {
+    let mut parser = ArgumentParser::new();
+    let error = parser.arg(Arg::new("--level").int().negatable()).unwrap_err();
+    assert_eq!(error, "Negatable arg 'level' must be a flag with a --name!");
+    let error = parser.arg(Arg::new("-c").flag().negatable()).unwrap_err();
+    assert_eq!(error, "Negatable arg 'c' must be a flag with a --name!");
+}
+
+fn repeated(policy: Option<DuplicatePolicy>) -> ArgumentParser {
+    let mut parser = ArgumentParser::new();
+    if let Some(policy) = policy {
+        parser.set_duplicate_policy(policy);
+    }
+    parser.arg(Arg::new("--name").string()).unwrap();
+    parser.arg(Arg::new("--quiet").store_false()).unwrap();
+    parser
+}
+
+#[test]
+fn duplicates_first_and_last_wins() {
+    let argv = ["p", "--name", "a", "--quiet", "--name", "b", "--quiet"];
+
+    let parsed = repeated(None).try_parse_from(argv).unwrap();
+    assert_eq!(parsed.get_value::<String>("name").as_deref(), Some("a"));
+    assert_eq!(parsed.get_value::<bool>("quiet"), Some(false));
+    assert_eq!(parsed.occurrences("name"), 2);
+
+    let parsed = repeated(Some(DuplicatePolicy::FirstWins)).try_parse_from(argv).unwrap();
+    assert_eq!(parsed.get_value::<String>("name").as_deref(), Some("a"));
+
+    let parsed = repeated(Some(DuplicatePolicy::LastWins)).try_parse_from(argv).unwrap();
+    assert_eq!(parsed.get_value::<String>("name").as_deref(), Some("b"));
+    assert_eq!(parsed.get_value::<bool>("quiet"), Some(false));
+}
+
+#[test]
+fn duplicates_are_errors() {
+    let parser = repeated(Some(DuplicatePolicy::Error));
+    let error = parser.try_parse_from(["p", "--name", "a", "--name", "b"]).unwrap_err();
+    assert_eq!(
+        error,
+        ParseError::DuplicateArgument {
+            arg: "name".to_owned(),
+            token: "--name".to_owned(),
+            position: 3,
+        }
+    );
+    let error = parser.try_parse_from(["p", "--quiet", "--name", "a", "--quiet"]).unwrap_err();
+    assert_eq!(
+        error,
+        ParseError::DuplicateArgument {
+            arg: "quiet".to_owned(),
+            token: "--quiet".to_owned(),
+            position: 4,
+        }
+    );
+    assert!(parser.try_parse_from(["p", "--name", "a", "--quiet"]).is_ok());
+}
+
+#[test]
+fn argument_duplicate_policy_overrides_the_parser() {
+    let mut parser = ArgumentParser::new();
+    parser.set_duplicate_policy(DuplicatePolicy::Error);
+    parser.arg(Arg::new("--name").string().duplicates(DuplicatePolicy::LastWins)).unwrap();
+    parser.arg(Arg::new("--level").int()).unwrap();
+
+    let parsed = parser.try_parse_from(["p", "--name", "a", "--name", "b"]).unwrap();
+    assert_eq!(parsed.get_value::<String>("name").as_deref(), Some("b"));
+    let error = parser.try_parse_from(["p", "--level", "1", "--level", "2"]).unwrap_err();
+    assert!(matches!(error, ParseError::DuplicateArgument { position: 3, .. }));
+}
+
+#[test]
+fn last_option_setter_wins() {
+    let mut parser = ArgumentParser::new();
+    parser
+        .arg(
+            Arg::new("--level")
+                .list(ListType::Int)
+                .help("first")
+                .help("second")
+                .metavar("A")
+                .metavar("B")
+                .nargs(1)
+                .nargs(2),
+        )
+        .unwrap();
+
+    let help = parser.format_help();
+    assert!(help.contains("--level B B  second"));
+    assert!(!help.contains("first"));
+    let parsed = parser.try_parse_from(["p", "--level", "1", "2"]).unwrap();
+    assert_eq!(parsed.get_value::<Vec<i32>>("level"), Some(vec![1, 2]));
+
+    let mut parser = ArgumentParser::new();
+    let name = Arg::new("--name").duplicates(DuplicatePolicy::Error).duplicates(DuplicatePolicy::LastWins);
+    parser.arg(name.env("MP_PARSE_TEST_UNSET").env("MP_PARSE_TEST_NAME")).unwrap();
+    std::env::set_var("MP_PARSE_TEST_NAME", "env");
+    let parsed = parser.try_parse_from(["p", "--name", "a", "--name", "b"]).unwrap();
+    assert_eq!(parsed.get_value::<String>("name").as_deref(), Some("b"));
+    let parsed = parser.try_parse_from(["p"]).unwrap();
+    assert_eq!(parsed.get_value::<String>("name").as_deref(), Some("env"));
+}
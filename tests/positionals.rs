@@ -0,0 +1,57 @@
+use migparser::{Arg, ArgumentParser, ListType, NArgs, ParseError};
+
+#[test]
+fn variable_list_before_a_single_positional() {
+    let mut parser = ArgumentParser::new();
+    parser.arg(Arg::new("src").list(ListType::String).num_args(NArgs::OneOrMore)).unwrap();
+    parser.arg(Arg::new("dst").string()).unwrap();
+    parser.arg(Arg::new("--force").alias("-f").flag()).unwrap();
+
+    let parsed = parser.try_parse_from(["cp", "a", "-f", "b", "c", "dir"]).unwrap();
+    let src = parsed.get_value::<Vec<String>>("src").unwrap();
+    assert_eq!(src, ["a", "b", "c"]);
+    assert_eq!(parsed.get_value::<String>("dst").as_deref(), Some("dir"));
+    assert_eq!(parsed.get_value::<bool>("force"), Some(true));
+
+    let parsed = parser.try_parse_from(["cp", "a", "dir"]).unwrap();
+    assert_eq!(parsed.get_value::<Vec<String>>("src").unwrap(), ["a"]);
+    assert_eq!(parsed.get_value::<String>("dst").as_deref(), Some("dir"));
+
+    /* src gets its minimum, dst is missing */
+    let error = parser.try_parse_from(["cp", "a"]).unwrap_err();
+    assert_eq!(error, ParseError::MissingRequired { arg: "dst".to_owned() });
+}
+
+#[test]
+fn optional_positionals() {
+    let mut parser = ArgumentParser::new();
+    parser.arg(Arg::new("input").string()).unwrap();
+    parser.arg(Arg::new("output").string().num_args(NArgs::Optional).default("out.txt")).unwrap();
+    parser.arg(Arg::new("level").int().num_args(NArgs::Optional)).unwrap();
+
+    let parsed = parser.try_parse_from(["p", "in.txt"]).unwrap();
+    assert_eq!(parsed.get_value::<String>("input").as_deref(), Some("in.txt"));
+    assert_eq!(parsed.get_value::<String>("output").as_deref(), Some("out.txt"));
+    assert_eq!(parsed.get_value::<i32>("level"), None);
+
+    let parsed = parser.try_parse_from(["p", "in.txt", "o.txt", "3"]).unwrap();
+    assert_eq!(parsed.get_value::<String>("output").as_deref(), Some("o.txt"));
+    assert_eq!(parsed.get_value::<i32>("level"), Some(3));
+
+    let error = parser.try_parse_from(["p", "in.txt", "o.txt", "3", "x"]).unwrap_err();
+    assert_eq!(error, ParseError::UnknownArgument { token: "x".to_owned(), position: 4 });
+}
+
+#[test]
+fn trailing_arguments() {
+    let mut parser = ArgumentParser::new();
+    parser.arg(Arg::new("--timeout").int()).unwrap();
+    parser.arg(Arg::new("command").list(ListType::String).trailing()).unwrap();
+
+    let argv = ["run", "--timeout", "5", "--", "cargo", "test", "--timeout", "1"];
+    let parsed = parser.try_parse_from(argv).unwrap();
+    assert_eq!(parsed.get_value::<i32>("timeout"), Some(5));
+    let command = parsed.get_value::<Vec<String>>("command").unwrap();
+    assert_eq!(command, ["cargo", "test", "--timeout", "1"]);
+    assert_eq!(parsed.get_trailing(), ["cargo", "test", "--timeout", "1"]);
+}
@@ -0,0 +1,32 @@
+use std::fs;
+
+use migparser::{Arg, ArgumentParser, ParseError};
+
+#[test]
+fn errors_point_at_the_response_file() {
+    let path = std::env::temp_dir().join(format!("migparser-{}-args.txt", std::process::id()));
+    fs::write(&path, "--level 3\n--level-typo 4\n").unwrap();
+    let mut parser = ArgumentParser::new();
+    parser.set_response_files(true);
+    parser.arg(Arg::new("--level").int()).unwrap();
+    parser.arg(Arg::new("--name").string()).unwrap();
+
+    let response = format!("@{}", path.display());
+    let error = parser.try_parse_from(["p", "--name", "x", &response]).unwrap_err();
+    assert_eq!(
+        error,
+        ParseError::InResponseFile {
+            path: path.display().to_string(),
+            line: 2,
+            error: Box::new(ParseError::UnknownArgument {
+                token: "--level-typo".to_owned(),
+                position: 5,
+            }),
+        }
+    );
+
+    /* Errors about argv tokens are left as they are */
+    let error = parser.try_parse_from(["p", &response, "--name"]).unwrap_err();
+    assert!(matches!(error, ParseError::NotEnoughValues { position: 5, .. }));
+    fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,88 @@
+use std::sync::{Arc, Mutex};
+
+use migparser::{Arg, ArgumentParser, DataType, OutputKind, ParseError, Reporter, ValueSource};
+
+/* Keeps what the parser prints */
+#[derive(Clone, Default)]
+struct Capture(Arc<Mutex<Vec<(OutputKind, String)>>>);
+
+impl Reporter for Capture {
+    fn write(&self, kind: OutputKind, text: &str) {
+        self.0.lock().unwrap().push((kind, text.to_owned()));
+    }
+}
+
+fn tool(capture: &Capture) -> ArgumentParser {
+    let mut build = ArgumentParser::new();
+    build.set_description("Builds the project");
+    build.arg(Arg::new("--jobs").alias("-j").int().required()).unwrap();
+    build.arg(Arg::new("target").string().default("all")).unwrap();
+
+    let mut parser = ArgumentParser::new();
+    parser.set_program_name("tool");
+    parser.set_reporter(capture.clone());
+    parser.arg(Arg::new("--env").string().required()).unwrap();
+    parser.add_subcommand("build", build).unwrap();
+    parser
+}
+
+#[test]
+fn dispatch_to_the_subcommand() {
+    let parser = tool(&Capture::default());
+
+    let parsed = parser.try_parse_from(["tool", "--env", "prod", "build", "-j", "3", "lib"]).unwrap();
+    assert_eq!(parsed.get_value::<String>("env").as_deref(), Some("prod"));
+    assert_eq!(parsed.get_subcommand(), Some("build"));
+    let build = parsed.get_subcommand_args().unwrap();
+    assert_eq!(build.get_value::<i32>("jobs"), Some(3));
+    assert_eq!(build.get_value::<String>("target").as_deref(), Some("lib"));
+    /* Positions are those of the whole command line */
+    assert_eq!(build.value_source("jobs"), Some(&ValueSource::CommandLine { index: 4 }));
+
+    let parsed = parser.try_parse_from(["tool", "--env", "prod"]).unwrap();
+    assert_eq!(parsed.get_subcommand(), None);
+}
+
+#[test]
+fn subcommand_checks_its_own_arguments() {
+    let parser = tool(&Capture::default());
+
+    let error = parser.try_parse_from(["tool", "--env", "prod", "build"]).unwrap_err();
+    assert_eq!(error, ParseError::MissingRequired { arg: "jobs".to_owned() });
+
+    let error = parser.try_parse_from(["tool", "--env", "prod", "build", "-j", "x"]).unwrap_err();
+    assert_eq!(
+        error,
+        ParseError::InvalidValue {
+            arg: "jobs".to_owned(),
+            raw: "x".to_owned(),
+            expected: DataType::Int,
+            position: 5,
+        }
+    );
+}
+
+#[test]
+fn subcommand_help_comes_first() {
+    let capture = Capture::default();
+    let parser = tool(&capture);
+
+    let error = parser.try_parse_from(["tool", "build", "-h"]).unwrap_err();
+    assert_eq!(error, ParseError::HelpRequested);
+    let output = capture.0.lock().unwrap();
+    assert_eq!(output.len(), 1);
+    assert_eq!(output[0].0, OutputKind::Help);
+    assert!(output[0].1.contains("tool build"));
+    assert!(output[0].1.contains("Builds the project"));
+}
+
+#[test]
+fn parent_help() {
+    let capture = Capture::default();
+    let parser = tool(&capture);
+
+    let error = parser.try_parse_from(["tool", "-h", "build"]).unwrap_err();
+    assert_eq!(error, ParseError::HelpRequested);
+    let output = capture.0.lock().unwrap();
+    assert!(output[0].1.starts_with("Usage: tool [-h] --env ENV {build} ..."));
+}